@@ -118,6 +118,34 @@ pub type smbc_smb_encrypt_level = c_uint;
 
 pub type smbc_bool = c_int;
 
+/// Change reported by `smbc_notify`
+#[repr(C)]
+#[derive(Copy)]
+pub struct smbc_notify_callback_action {
+    /** One of the SMBC_NOTIFY_ACTION_* values
+     */
+    pub action: u32,
+
+    /** Name of the changed file, relative to the watched directory
+     */
+    pub filename: *const c_char,
+}
+
+impl clone::Clone for smbc_notify_callback_action {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+pub const SMBC_NOTIFY_ACTION_ADDED: u32 = 1;
+pub const SMBC_NOTIFY_ACTION_REMOVED: u32 = 2;
+pub const SMBC_NOTIFY_ACTION_MODIFIED: u32 = 3;
+pub const SMBC_NOTIFY_ACTION_OLD_NAME: u32 = 4;
+pub const SMBC_NOTIFY_ACTION_NEW_NAME: u32 = 5;
+pub const SMBC_NOTIFY_ACTION_ADDED_STREAM: u32 = 6;
+pub const SMBC_NOTIFY_ACTION_REMOVED_STREAM: u32 = 7;
+pub const SMBC_NOTIFY_ACTION_MODIFIED_STREAM: u32 = 8;
+
 #[repr(C)]
 #[derive(Copy)]
 pub struct print_job_info {
@@ -240,6 +268,37 @@ pub type smbc_write_fn = option::Option<
         count: size_t,
     ) -> ssize_t,
 >;
+pub type smbc_splice_cb = option::Option<extern "C" fn(n: off_t, private: *mut c_void) -> c_int>;
+pub type smbc_splice_fn = option::Option<
+    extern "C" fn(
+        c: *mut SMBCCTX,
+        srcfile: *mut SMBCFILE,
+        dstfile: *mut SMBCFILE,
+        count: off_t,
+        splice_cb: smbc_splice_cb,
+        private: *mut c_void,
+    ) -> off_t,
+>;
+pub type smbc_notify_callback_fn = option::Option<
+    extern "C" fn(
+        actions: *const smbc_notify_callback_action,
+        num_actions: size_t,
+        private: *mut c_void,
+    ) -> c_int,
+>;
+pub type smbc_notify_fn = option::Option<
+    extern "C" fn(
+        c: *mut SMBCCTX,
+        dir: *mut SMBCFILE,
+        recursive: smbc_bool,
+        completion_filter: u32,
+        callback_timeout_ms: c_uint,
+        cb: smbc_notify_callback_fn,
+        private: *mut c_void,
+    ) -> c_int,
+>;
+pub type smbc_debug_callback_fn =
+    option::Option<extern "C" fn(private_ptr: *mut c_void, level: c_int, msg: *const c_char)>;
 pub type smbc_unlink_fn =
     option::Option<extern "C" fn(c: *mut SMBCCTX, fname: *const c_char) -> c_int>;
 pub type smbc_rename_fn = option::Option<
@@ -253,6 +312,8 @@ pub type smbc_rename_fn = option::Option<
 pub type smbc_lseek_fn = option::Option<
     extern "C" fn(c: *mut SMBCCTX, file: *mut SMBCFILE, offset: off_t, whence: c_int) -> off_t,
 >;
+pub type smbc_ftruncate_fn =
+    option::Option<extern "C" fn(c: *mut SMBCCTX, file: *mut SMBCFILE, size: off_t) -> c_int>;
 pub type smbc_stat_fn =
     option::Option<extern "C" fn(c: *mut SMBCCTX, fname: *const c_char, st: *mut stat) -> c_int>;
 pub type smbc_statvfs_fn =
@@ -269,6 +330,9 @@ pub type smbc_readdir_fn =
     option::Option<extern "C" fn(c: *mut SMBCCTX, dir: *mut SMBCFILE) -> *mut smbc_dirent>;
 pub type smbc_readdirplus_fn =
     option::Option<extern "C" fn(c: *mut SMBCCTX, dir: *mut SMBCFILE) -> *mut libsmb_file_info>;
+pub type smbc_readdirplus2_fn = option::Option<
+    extern "C" fn(c: *mut SMBCCTX, dir: *mut SMBCFILE, st: *mut stat) -> *const libsmb_file_info,
+>;
 pub type smbc_getdents_fn = option::Option<
     extern "C" fn(
         c: *mut SMBCCTX,
@@ -440,6 +504,12 @@ impl default::Default for _smbc_options {
 #[link(name = "smbclient")]
 extern "C" {
     pub fn smbc_setDebug(c: *mut SMBCCTX, debug: c_int);
+    pub fn smbc_getDebug(c: *mut SMBCCTX) -> c_int;
+    pub fn smbc_setLogCallback(
+        c: *mut SMBCCTX,
+        private_ptr: *mut c_void,
+        fn_: smbc_debug_callback_fn,
+    );
     pub fn smbc_getNetbiosName(c: *mut SMBCCTX) -> *mut c_char;
     pub fn smbc_setNetbiosName(c: *mut SMBCCTX, netbios_name: *mut c_char);
     pub fn smbc_getWorkgroup(c: *mut SMBCCTX) -> *mut c_char;
@@ -448,17 +518,46 @@ extern "C" {
     pub fn smbc_setUser(c: *mut SMBCCTX, user: *mut c_char);
     pub fn smbc_getTimeout(c: *mut SMBCCTX) -> c_int;
     pub fn smbc_setTimeout(c: *mut SMBCCTX, timeout: c_int);
+    pub fn smbc_getPort(c: *mut SMBCCTX) -> u16;
+    pub fn smbc_setPort(c: *mut SMBCCTX, port: u16);
     pub fn smbc_setOptionDebugToStderr(c: *mut SMBCCTX, b: smbc_bool);
     pub fn smbc_setOptionOpenShareMode(c: *mut SMBCCTX, share_mode: smbc_share_mode);
+    pub fn smbc_getOptionOpenShareMode(c: *mut SMBCCTX) -> smbc_share_mode;
     pub fn smbc_setOptionSmbEncryptionLevel(c: *mut SMBCCTX, level: smbc_smb_encrypt_level);
+    pub fn smbc_getOptionSmbEncryptionLevel(c: *mut SMBCCTX) -> smbc_smb_encrypt_level;
     pub fn smbc_setOptionCaseSensitive(c: *mut SMBCCTX, b: smbc_bool);
+    pub fn smbc_getOptionCaseSensitive(c: *mut SMBCCTX) -> smbc_bool;
     pub fn smbc_setOptionBrowseMaxLmbCount(c: *mut SMBCCTX, count: c_int);
+    pub fn smbc_getOptionBrowseMaxLmbCount(c: *mut SMBCCTX) -> c_int;
     pub fn smbc_setOptionUrlEncodeReaddirEntries(c: *mut SMBCCTX, b: smbc_bool);
+    pub fn smbc_getOptionUrlEncodeReaddirEntries(c: *mut SMBCCTX) -> smbc_bool;
     pub fn smbc_setOptionOneSharePerServer(c: *mut SMBCCTX, b: smbc_bool);
+    pub fn smbc_getOptionOneSharePerServer(c: *mut SMBCCTX) -> smbc_bool;
     pub fn smbc_setOptionUseKerberos(c: *mut SMBCCTX, b: smbc_bool);
+    pub fn smbc_getOptionUseKerberos(c: *mut SMBCCTX) -> smbc_bool;
     pub fn smbc_setOptionFallbackAfterKerberos(c: *mut SMBCCTX, b: smbc_bool);
+    pub fn smbc_getOptionFallbackAfterKerberos(c: *mut SMBCCTX) -> smbc_bool;
     pub fn smbc_setOptionNoAutoAnonymousLogin(c: *mut SMBCCTX, b: smbc_bool);
+    pub fn smbc_getOptionNoAutoAnonymousLogin(c: *mut SMBCCTX) -> smbc_bool;
     pub fn smbc_setOptionUseCCache(c: *mut SMBCCTX, b: smbc_bool);
+    pub fn smbc_getOptionUseCCache(c: *mut SMBCCTX) -> smbc_bool;
+    pub fn smbc_setOptionProtocols(
+        c: *mut SMBCCTX,
+        min_proto: *const c_char,
+        max_proto: *const c_char,
+    ) -> smbc_bool;
+    pub fn smbc_setOptionFullTimeNames(c: *mut SMBCCTX, b: smbc_bool);
+    pub fn smbc_getOptionFullTimeNames(c: *mut SMBCCTX) -> smbc_bool;
+    pub fn smbc_setOptionPosixExtensions(c: *mut SMBCCTX, b: smbc_bool);
+    pub fn smbc_getOptionPosixExtensions(c: *mut SMBCCTX) -> smbc_bool;
+    pub fn smbc_getFunctionRemoveUnusedServer(c: *mut SMBCCTX) -> smbc_remove_unused_server_fn;
+    pub fn smbc_getFunctionAddCachedServer(c: *mut SMBCCTX) -> smbc_add_cached_srv_fn;
+    pub fn smbc_setFunctionAddCachedServer(c: *mut SMBCCTX, _fn: smbc_add_cached_srv_fn);
+    pub fn smbc_getFunctionGetCachedServer(c: *mut SMBCCTX) -> smbc_get_cached_srv_fn;
+    pub fn smbc_setFunctionGetCachedServer(c: *mut SMBCCTX, _fn: smbc_get_cached_srv_fn);
+    pub fn smbc_getFunctionRemoveCachedServer(c: *mut SMBCCTX) -> smbc_remove_cached_srv_fn;
+    pub fn smbc_setFunctionRemoveCachedServer(c: *mut SMBCCTX, _fn: smbc_remove_cached_srv_fn);
+    pub fn smbc_getFunctionPurgeCachedServers(c: *mut SMBCCTX) -> smbc_purge_cached_fn;
     pub fn smbc_setFunctionAuthDataWithContext(
         c: *mut SMBCCTX,
         _fn: smbc_get_auth_data_with_context_fn,
@@ -468,7 +567,10 @@ extern "C" {
     pub fn smbc_getFunctionWrite(c: *mut SMBCCTX) -> smbc_write_fn;
     pub fn smbc_getFunctionUnlink(c: *mut SMBCCTX) -> smbc_unlink_fn;
     pub fn smbc_getFunctionRename(c: *mut SMBCCTX) -> smbc_rename_fn;
+    pub fn smbc_getFunctionUtimes(c: *mut SMBCCTX) -> smbc_utimes_fn;
     pub fn smbc_getFunctionLseek(c: *mut SMBCCTX) -> smbc_lseek_fn;
+    pub fn smbc_getFunctionFtruncate(c: *mut SMBCCTX) -> smbc_ftruncate_fn;
+    pub fn smbc_getFunctionSplice(c: *mut SMBCCTX) -> smbc_splice_fn;
     pub fn smbc_getFunctionStat(c: *mut SMBCCTX) -> smbc_stat_fn;
     pub fn smbc_getFunctionStatVFS(c: *mut SMBCCTX) -> smbc_statvfs_fn;
     pub fn smbc_getFunctionClose(c: *mut SMBCCTX) -> smbc_close_fn;
@@ -476,11 +578,23 @@ extern "C" {
     pub fn smbc_getFunctionClosedir(c: *mut SMBCCTX) -> smbc_closedir_fn;
     pub fn smbc_getFunctionReaddir(c: *mut SMBCCTX) -> smbc_readdir_fn;
     pub fn smbc_getFunctionReaddirPlus(c: *mut SMBCCTX) -> smbc_readdirplus_fn;
+    pub fn smbc_getFunctionReaddirPlus2(c: *mut SMBCCTX) -> smbc_readdirplus2_fn;
+    pub fn smbc_getFunctionTelldir(c: *mut SMBCCTX) -> smbc_telldir_fn;
+    pub fn smbc_getFunctionLseekdir(c: *mut SMBCCTX) -> smbc_lseekdir_fn;
+    pub fn smbc_getFunctionNotify(c: *mut SMBCCTX) -> smbc_notify_fn;
     pub fn smbc_getFunctionMkdir(c: *mut SMBCCTX) -> smbc_mkdir_fn;
     pub fn smbc_getFunctionRmdir(c: *mut SMBCCTX) -> smbc_rmdir_fn;
     pub fn smbc_getFunctionChmod(c: *mut SMBCCTX) -> smbc_chmod_fn;
+    pub fn smbc_getFunctionSetxattr(c: *mut SMBCCTX) -> smbc_setxattr_fn;
+    pub fn smbc_getFunctionGetxattr(c: *mut SMBCCTX) -> smbc_getxattr_fn;
+    pub fn smbc_getFunctionRemovexattr(c: *mut SMBCCTX) -> smbc_removexattr_fn;
+    pub fn smbc_getFunctionListxattr(c: *mut SMBCCTX) -> smbc_listxattr_fn;
     pub fn smbc_getFunctionPrintFile(c: *mut SMBCCTX) -> smbc_print_file_fn;
+    pub fn smbc_getFunctionOpenPrintJob(c: *mut SMBCCTX) -> smbc_open_print_job_fn;
+    pub fn smbc_getFunctionListPrintJobs(c: *mut SMBCCTX) -> smbc_list_print_jobs_fn;
+    pub fn smbc_getFunctionUnlinkPrintJob(c: *mut SMBCCTX) -> smbc_unlink_print_job_fn;
     pub fn smbc_new_context() -> *mut SMBCCTX;
+    pub fn smbc_setConfiguration(c: *mut SMBCCTX, file: *const c_char) -> c_int;
     pub fn smbc_free_context(context: *mut SMBCCTX, shutdown_ctx: c_int) -> c_int;
     pub fn smbc_init_context(context: *mut SMBCCTX) -> *mut SMBCCTX;
     pub fn smbc_version() -> *const c_char;
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use argh::FromArgs;
-use pavao::{SmbClient, SmbCredentials, SmbDirentInfo, SmbDirentType, SmbOptions};
+use pavao::{SmbClient, SmbCredentials, SmbDirentStat, SmbDirentType, SmbOptions};
 
 #[derive(FromArgs)]
 #[argh(description = "
@@ -49,7 +49,7 @@ fn read_secret_from_tty(prompt: &str) -> std::io::Result<String> {
 }
 
 fn treeplus(client: &SmbClient, uri: &str, depth: usize) {
-    let vec = client.list_dirplus(uri).unwrap();
+    let vec = client.list_dirplus2(uri).unwrap();
     for entityplus in vec.into_iter() {
         let entityplus_uri = entityplus_uri(&entityplus, uri);
         print_entry_plus(&entityplus, depth);
@@ -60,19 +60,20 @@ fn treeplus(client: &SmbClient, uri: &str, depth: usize) {
     }
 }
 
-fn entityplus_uri(entity: &SmbDirentInfo, path: &str) -> String {
+fn entityplus_uri(entity: &SmbDirentStat, path: &str) -> String {
     let mut p = PathBuf::from(path);
     p.push(PathBuf::from(entity.name()));
     p.as_path().to_string_lossy().to_string()
 }
 
-fn print_entry_plus(entityplus: &SmbDirentInfo, depth: usize) {
+fn print_entry_plus(entityplus: &SmbDirentStat, depth: usize) {
     println!(
-        "{}{:32}\t{}\t{:x}\t{:?}",
+        "{}{:32}\t{}\t{:x}\t{:o}\t{:?}",
         fmt_depth(depth),
         entityplus.name(),
-        entityplus.size,
-        entityplus.attrs,
+        entityplus.info.size,
+        entityplus.info.attrs,
+        libc::mode_t::from(entityplus.stat.mode),
         entityplus.get_type(),
     )
 }
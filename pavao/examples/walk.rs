@@ -0,0 +1,75 @@
+use argh::FromArgs;
+use pavao::{SmbClient, SmbCredentials, SmbOptions, SmbWalkEntry, SmbWalkOrder};
+
+#[derive(FromArgs)]
+#[argh(description = "
+where positional can be: [smb://address[:port]]
+
+Please, report issues to <https://github.com/veeso/pavao>
+Please, consider supporting the author <https://ko-fi.com/veeso>")]
+struct Args {
+    #[argh(option, short = 'P', description = "specify password")]
+    password: Option<String>,
+    #[argh(option, short = 'u', description = "specify username")]
+    username: String,
+    #[argh(option, short = 'w', description = "specify workgroup")]
+    workgroup: String,
+    #[argh(option, short = 's', description = "specify share")]
+    share: String,
+    #[argh(option, short = 'd', description = "specify max depth")]
+    max_depth: Option<usize>,
+    #[argh(switch, short = 'b', description = "walk breadth first")]
+    breadth_first: bool,
+    #[argh(positional, description = "smb://address[:port]")]
+    server: String,
+}
+
+fn main() {
+    assert!(env_logger::builder().try_init().is_ok());
+    let args: Args = argh::from_env();
+    let password = match args.password {
+        Some(p) => p,
+        None => read_secret_from_tty("Password: ").ok().unwrap(),
+    };
+    //setup server
+    let client = SmbClient::new(
+        SmbCredentials::default()
+            .server(args.server)
+            .share(args.share)
+            .password(password)
+            .username(args.username)
+            .workgroup(args.workgroup),
+        SmbOptions::default().one_share_per_server(true),
+    )
+    .unwrap();
+    let order = match args.breadth_first {
+        true => SmbWalkOrder::BreadthFirst,
+        false => SmbWalkOrder::DepthFirst,
+    };
+    let walk = client
+        .walk("/")
+        .order(order)
+        .max_depth(args.max_depth.unwrap_or(usize::MAX))
+        .sort_by(|a, b| a.name().cmp(b.name()));
+    for entry in walk {
+        match entry {
+            Ok(entry) => print_entry(&entry),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+}
+
+/// Read a secret from tty with customisable prompt
+fn read_secret_from_tty(prompt: &str) -> std::io::Result<String> {
+    rpassword::prompt_password(prompt)
+}
+
+fn print_entry(entry: &SmbWalkEntry) {
+    println!(
+        "{}{:32}\t{}\t{:?}",
+        " ".repeat((entry.depth - 1) * 4),
+        entry.name(),
+        entry.info.size,
+        entry.get_type(),
+    )
+}
@@ -23,6 +23,37 @@ pub enum SmbError {
     NulInPath(NulError),
     #[error("mutex error")]
     Mutex,
+    #[error("invalid option: {0}")]
+    BadOption(String),
+    #[error("no such extended attribute: {0}")]
+    NoSuchXattr(String),
+    #[error("extended attribute already exists: {0}")]
+    XattrExists(String),
+    #[error("operation not supported by the server")]
+    NotSupported,
+    #[error("{path}: {error}")]
+    Path { path: String, error: Box<SmbError> },
+    #[error("{} errors occurred", .0.len())]
+    Multiple(Vec<SmbError>),
+}
+
+impl SmbError {
+    /// Wrap `error` with the `path` of the entry which caused it
+    pub(crate) fn with_path<S: AsRef<str>>(error: SmbError, path: S) -> Self {
+        Self::Path {
+            path: path.as_ref().to_string(),
+            error: Box::new(error),
+        }
+    }
+
+    /// Returns the raw os error code, if this error was caused by an os error
+    pub fn raw_os_error(&self) -> Option<i32> {
+        match self {
+            Self::Io(e) => e.raw_os_error(),
+            Self::Path { error, .. } => error.raw_os_error(),
+            _ => None,
+        }
+    }
 }
 
 impl PartialEq for SmbError {
@@ -32,6 +63,19 @@ impl PartialEq for SmbError {
             (Self::BadValue, Self::BadValue) => true,
             (Self::Io(io), Self::Io(io2)) => io.kind() == io2.kind(),
             (Self::NulInPath(e), Self::NulInPath(e2)) => e == e2,
+            (Self::Mutex, Self::Mutex) => true,
+            (Self::BadOption(a), Self::BadOption(b)) => a == b,
+            (Self::NoSuchXattr(a), Self::NoSuchXattr(b)) => a == b,
+            (Self::XattrExists(a), Self::XattrExists(b)) => a == b,
+            (Self::NotSupported, Self::NotSupported) => true,
+            (
+                Self::Path { path, error },
+                Self::Path {
+                    path: path2,
+                    error: error2,
+                },
+            ) => path == path2 && error == error2,
+            (Self::Multiple(a), Self::Multiple(b)) => a == b,
             (_, _) => false,
         }
     }
@@ -58,8 +58,16 @@ pub(crate) mod utils;
 // -- exports
 pub use error::{SmbError, SmbResult};
 pub use smb::{
-    SmbClient, SmbCredentials, SmbDirent, SmbDirentInfo, SmbDirentType, SmbEncryptionLevel,
-    SmbFile, SmbMode, SmbModeClass, SmbOpenOptions, SmbOptions, SmbShareMode, SmbStat, SmbStatVfs,
+    SmbAccessMask, SmbAce, SmbAceFlags, SmbAceType, SmbAddressFamily, SmbCacheStats, SmbClient,
+    SmbCredentials, SmbDirPosition, SmbDirent, SmbDirentInfo, SmbDirentStat, SmbDirentType,
+    SmbEffectiveOptions, SmbEncryptionLevel, SmbFile, SmbFileAttributes, SmbMode, SmbModeClass,
+    SmbMoveOptions, SmbMoveStrategy, SmbNameResolver, SmbNotifyFilter, SmbOpenOptions, SmbOptions,
+    SmbPrintJob, SmbPrintJobInfo, SmbProtocol, SmbReadDir, SmbRemoveDirOptions,
+    SmbSecurityDescriptor, SmbServer, SmbSessionSecurity, SmbShare, SmbShareMode, SmbShareType,
+    SmbSigningPolicy, SmbSnapshot, SmbStat, SmbStatVfs, SmbStreamInfo, SmbTransferOptions,
+    SmbTransferProgress, SmbTransferSummary, SmbTransport, SmbTrustee, SmbWalk, SmbWalkEntry,
+    SmbWalkOrder, SmbWatch, SmbWatchEvent, SmbWatchOptions, SmbWatchStopper, SmbWorkgroup,
+    SmbXattrFlag,
 };
 
 // -- mock
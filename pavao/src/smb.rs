@@ -4,9 +4,12 @@
 
 mod auth_service;
 mod client;
+mod server_cache;
+mod symbols;
 mod types;
 
 // -- priv
 use auth_service::AuthService;
 pub use client::SmbClient;
+use server_cache::ServerCache;
 pub use types::*;
@@ -2,21 +2,64 @@
 //!
 //! module which exposes the Smb Client
 
+use std::cell::RefCell;
+use std::fs::{File, FileTimes, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 use std::{mem, ptr};
 
-use libc::{self, c_char, c_int};
+use libc::{self, c_char, c_int, c_void, off_t};
 use pavao_sys::{SMBCCTX, *};
 
+use super::symbols;
+use super::types::stream::stream_path;
+use super::types::transfer::transfer;
 use super::{
-    AuthService, SmbCredentials, SmbDirentInfo, SmbFile, SmbMode, SmbOpenOptions, SmbOptions,
-    SmbStat, SmbStatVfs,
+    AuthService, ServerCache, SmbCacheStats, SmbCredentials, SmbDirentInfo, SmbDirentStat,
+    SmbEffectiveOptions, SmbEncryptionLevel, SmbFile, SmbFileAttributes, SmbMode, SmbMoveOptions,
+    SmbMoveStrategy, SmbOpenOptions, SmbOptions, SmbPrintJob, SmbPrintJobInfo, SmbProtocol,
+    SmbReadDir, SmbRemoveDirOptions, SmbServer, SmbSessionSecurity, SmbShare, SmbShareMode,
+    SmbSigningPolicy, SmbSnapshot, SmbStat, SmbStatVfs, SmbStreamInfo, SmbTransferOptions,
+    SmbTransferProgress, SmbTransferSummary, SmbWalk, SmbWatch, SmbWatchOptions, SmbWorkgroup,
+    SmbXattrFlag,
 };
-use crate::{utils, SmbDirent, SmbError, SmbResult};
+use crate::{utils, SmbAce, SmbDirent, SmbDirentType, SmbError, SmbResult, SmbSecurityDescriptor};
+
+/// Scheme of smb URIs; listing it alone yields the workgroups
+const SMB_URI_SCHEME: &str = "smb://";
+
+/// Directory where `vfs_shadow_copy2` keeps the snapshots by default
+const DEFAULT_SNAPDIR: &str = ".snapshots";
+
+/// Buffer size used to copy files when server-side copy is not available
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Xattr holding the DOS attributes of a file
+const XATTR_DOS_ATTR_MODE: &str = "system.dos_attr.mode";
+/// Xattr holding the creation time of a file; it only exists with `full_time_names`
+const XATTR_DOS_ATTR_CREATE_TIME: &str = "system.dos_attr.create_time";
+/// Xattr holding the whole NT security descriptor with SIDs
+const XATTR_NT_SEC_DESC: &str = "system.nt_sec_desc.*";
+/// Xattr holding the whole NT security descriptor with resolved names
+const XATTR_NT_SEC_DESC_NAMES: &str = "system.nt_sec_desc.*+";
+
+/// Initial buffer size used to read xattrs when the size can't be probed
+const XATTR_BUFFER_SIZE: usize = 1024;
+/// Max buffer size used to read xattrs
+const XATTR_BUFFER_MAX_SIZE: usize = 1024 * 1024;
+
+/// errno set by libsmbclient when an xattr doesn't exist
+#[cfg(any(target_os = "macos", target_os = "openbsd"))]
+const ENOATTR: c_int = libc::ENOATTR;
+#[cfg(not(any(target_os = "macos", target_os = "openbsd")))]
+const ENOATTR: c_int = libc::ENODATA;
 
 struct SmbContext {
     inner: *mut SMBCCTX,
+    options: SmbOptions,
 }
 
 impl SmbContext {
@@ -24,12 +67,14 @@ impl SmbContext {
     fn null() -> Self {
         SmbContext {
             inner: ptr::null_mut(),
+            options: SmbOptions::default(),
         }
     }
 
-    /// Set context
-    pub fn set(&mut self, ctx: *mut SMBCCTX) {
+    /// Set context and the options it has been configured with
+    pub fn set(&mut self, ctx: *mut SMBCCTX, options: SmbOptions) {
         self.inner = ctx;
+        self.options = options;
     }
 
     /// Get context
@@ -46,21 +91,51 @@ impl SmbContext {
 unsafe impl Sync for SmbContext {}
 unsafe impl Send for SmbContext {}
 
+thread_local! {
+    /// Jobs collected by the `list_print_jobs` callback
+    static PRINT_JOBS: RefCell<Vec<SmbPrintJobInfo>> = const { RefCell::new(Vec::new()) };
+    /// Errors reported by libsmbclient while loading a configuration
+    static CONFIG_ERRORS: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
 lazy_static! {
     static ref AUTH_SERVICE: Mutex<AuthService> = Mutex::new(AuthService::default());
     static ref SMBCTX: Arc<Mutex<SmbContext>> = Arc::new(Mutex::new(SmbContext::null()));
+    static ref SERVER_CACHE: Mutex<ServerCache> = Mutex::new(ServerCache::default());
+}
+
+/// State passed to the `splice` callback
+struct SpliceState<'a> {
+    total: u64,
+    progress: &'a mut dyn FnMut(u64, u64),
 }
 
 /// Smb protocol client
+///
+/// # Limitations
+///
+/// Some server features can't be reached through the libsmbclient API:
+///
+/// - the alternate data streams of a file can't be listed, since the stream information class isn't exposed;
+///   a stream can only be accessed by name
+/// - whether the SMB3.1.1 POSIX extensions have been negotiated isn't reported: only the requested value
+///   can be read back with [`SmbClient::options`]
+/// - symlinks can't be created nor read, even with the POSIX extensions, since there's neither a symlink
+///   nor a reparse point request
+/// - the dialect negotiated with a server isn't reported: only the range set by [`SmbOptions::min_protocol`]
+///   and [`SmbOptions::max_protocol`] applies, and it can't be read back
 pub struct SmbClient {
+    server: String,
     uri: String,
 }
 
 impl SmbClient {
     /// Initialize a new `SmbClient` with the provided credentials to connect to the remote smb server
     pub fn new(credentials: SmbCredentials, options: SmbOptions) -> SmbResult<Self> {
-        let uri = Self::build_uri(credentials.server.as_str(), credentials.share.as_str());
-        let smbc = SmbClient { uri };
+        options.validate()?;
+        let server = options.server_url(credentials.server.as_str())?;
+        let uri = Self::build_uri(server.as_str(), credentials.share.as_str());
+        let smbc = SmbClient { server, uri };
         // insert credentials
         trace!("creating context...");
         // get current context
@@ -72,7 +147,11 @@ impl SmbClient {
                 // set options
                 trace!("configuring client options");
                 smbc_setFunctionAuthDataWithContext(ctx, Some(Self::auth_wrapper));
-                Self::setup_options(ctx, options);
+                if let Err(e) = Self::setup_options(ctx, options.clone()) {
+                    error!("failed to configure client options: {}", e);
+                    smbc_free_context(ctx, 0);
+                    return Err(e);
+                }
 
                 // set ctx
                 let smb_ctx = utils::result_from_ptr_mut(smbc_init_context(ctx))?;
@@ -83,12 +162,100 @@ impl SmbClient {
                     .insert(Self::auth_service_uuid(smb_ctx), credentials);
 
                 // set context
-                ctx_lock.set(smb_ctx);
+                ctx_lock.set(smb_ctx, options);
             }
         }
         Ok(smbc)
     }
 
+    /// Set the libsmbclient debug level, from 0 (errors only) to 10
+    pub fn set_debug_level(&self, level: i32) -> SmbResult<()> {
+        trace!("setting debug level to {}", level);
+        unsafe { smbc_setDebug(self.ctx()?, level) };
+        Ok(())
+    }
+
+    /// Get the libsmbclient debug level
+    pub fn get_debug_level(&self) -> SmbResult<i32> {
+        Ok(unsafe { smbc_getDebug(self.ctx()?) })
+    }
+
+    /// Close the cached server connections which have no open files or directories.
+    ///
+    /// Returns the number of connections closed
+    pub fn purge_cached_connections(&self) -> SmbResult<usize> {
+        trace!("purging cached connections");
+        let ctx = self.ctx()?;
+        let cached = self.cache_stats()?.cached;
+        let purge = unsafe { smbc_getFunctionPurgeCachedServers(ctx) }.ok_or(SmbError::BadValue)?;
+        if purge(ctx) != 0 {
+            debug!("some cached connections are in use and have been kept");
+        }
+        Ok(cached.saturating_sub(self.cache_stats()?.cached))
+    }
+
+    /// Get the statistics of the server connections cached by libsmbclient
+    pub fn cache_stats(&self) -> SmbResult<SmbCacheStats> {
+        Ok(SERVER_CACHE.lock().map_err(|_| SmbError::Mutex)?.stats())
+    }
+
+    /// Get the security required on the sessions by the configured signing policy and encryption level.
+    ///
+    /// This reflects the configuration only: libsmbclient doesn't report what has been negotiated,
+    /// so the security of an established session can't be verified.
+    /// The session security parameters can't be set by [`SmbOptions::parameter`] nor [`SmbOptions::config_file`],
+    /// but a system smb.conf loaded by libsmbclient may still require more than reported
+    pub fn required_session_security(&self) -> SmbResult<SmbSessionSecurity> {
+        let ctx = SMBCTX.lock().map_err(|_| SmbError::Mutex)?;
+        let encrypted = unsafe { smbc_getOptionSmbEncryptionLevel(ctx.get()) }
+            == smbc_smb_encrypt_level::from(SmbEncryptionLevel::Require);
+        let signed = encrypted || ctx.options.signing == Some(SmbSigningPolicy::Required);
+        Ok(SmbSessionSecurity { signed, encrypted })
+    }
+
+    /// Get the settings in effect on the client context, read back from libsmbclient.
+    ///
+    /// Since all the clients share the same context, these may differ from the options passed to [`SmbClient::new`]
+    pub fn options(&self) -> SmbResult<SmbEffectiveOptions> {
+        trace!("getting effective options");
+        let ctx = self.ctx()?;
+        unsafe {
+            Ok(SmbEffectiveOptions {
+                browser_max_lmb_count: smbc_getOptionBrowseMaxLmbCount(ctx),
+                case_sensitive: smbc_getOptionCaseSensitive(ctx) != 0,
+                debug_level: smbc_getDebug(ctx),
+                encryption_level: SmbEncryptionLevel::try_from(smbc_getOptionSmbEncryptionLevel(
+                    ctx,
+                ))?,
+                fallback_after_kerberos: smbc_getOptionFallbackAfterKerberos(ctx) != 0,
+                full_time_names: smbc_getOptionFullTimeNames(ctx) != 0,
+                netbios_name: Self::ctx_string(smbc_getNetbiosName(ctx))?,
+                no_auto_anonymous_login: smbc_getOptionNoAutoAnonymousLogin(ctx) != 0,
+                one_share_per_server: smbc_getOptionOneSharePerServer(ctx) != 0,
+                open_share_mode: SmbShareMode::try_from(smbc_getOptionOpenShareMode(ctx))?,
+                port: match smbc_getPort(ctx) {
+                    0 => None,
+                    port => Some(port),
+                },
+                posix_extensions: symbols::get_option_posix_extensions(ctx).unwrap_or(0) != 0,
+                timeout: Duration::from_millis(smbc_getTimeout(ctx) as u64),
+                url_encode_readdir_entries: smbc_getOptionUrlEncodeReaddirEntries(ctx) != 0,
+                use_ccache: smbc_getOptionUseCCache(ctx) != 0,
+                use_kerberos: smbc_getOptionUseKerberos(ctx) != 0,
+                user: Self::ctx_string(smbc_getUser(ctx))?,
+                workgroup: Self::ctx_string(smbc_getWorkgroup(ctx))?,
+            })
+        }
+    }
+
+    /// Convert a string owned by the context; a null pointer is an empty string
+    unsafe fn ctx_string(ptr: *mut c_char) -> SmbResult<String> {
+        if ptr.is_null() {
+            return Ok(String::new());
+        }
+        utils::char_ptr_to_string(ptr).map_err(|_| SmbError::BadValue)
+    }
+
     /// Get netbios name from server
     pub fn get_netbios_name(&self) -> SmbResult<String> {
         trace!("getting netbios name");
@@ -182,14 +349,52 @@ impl SmbClient {
         utils::to_result_with_ioerror((), unlink_fn(self.ctx()?, path.as_ptr()))
     }
 
+    /// Stat file at `path` as of `snapshot`
+    pub fn stat_at<S>(&self, path: S, snapshot: SmbSnapshot) -> SmbResult<SmbStat>
+    where
+        S: AsRef<str>,
+    {
+        self.stat(snapshot.path(path.as_ref()))
+    }
+
+    /// Get the size of the alternate data stream `name` of the file at `path`.
+    ///
+    /// libsmbclient doesn't expose the stream information class, neither through xattrs nor a listing,
+    /// so the streams of a file can't be enumerated: the name of the stream must be known (e.g. `Zone.Identifier`)
+    pub fn stream_info<S>(&self, path: S, name: S) -> SmbResult<SmbStreamInfo>
+    where
+        S: AsRef<str>,
+    {
+        trace!("getting stream {} of {}", name.as_ref(), path.as_ref());
+        let stat = self.stat(stream_path(path.as_ref(), name.as_ref())?)?;
+        Ok(SmbStreamInfo {
+            name: name.as_ref().to_string(),
+            size: stat.size,
+        })
+    }
+
+    /// Remove the alternate data stream `name` of the file at `path`
+    pub fn remove_stream<S>(&self, path: S, name: S) -> SmbResult<()>
+    where
+        S: AsRef<str>,
+    {
+        trace!("removing stream {} of {}", name.as_ref(), path.as_ref());
+        self.unlink(stream_path(path.as_ref(), name.as_ref())?)
+    }
+
     /// Rename file at `orig_url` to `new_url`
     pub fn rename<S>(&self, orig_url: S, new_url: S) -> SmbResult<()>
     where
         S: AsRef<str>,
     {
         trace!("renaming {} to {}", orig_url.as_ref(), new_url.as_ref());
-        let orig_url = utils::str_to_cstring(self.uri(orig_url))?;
-        let new_url = utils::str_to_cstring(self.uri(new_url))?;
+        self.rename_uri(self.uri(orig_url), self.uri(new_url))
+    }
+
+    /// Rename the entry at the absolute `orig_url` to the absolute `new_url`
+    fn rename_uri(&self, orig_url: String, new_url: String) -> SmbResult<()> {
+        let orig_url = utils::str_to_cstring(orig_url)?;
+        let new_url = utils::str_to_cstring(new_url)?;
         let rename_fn = self.get_fn(self.ctx()?, smbc_getFunctionRename)?;
         utils::to_result_with_ioerror(
             (),
@@ -208,46 +413,69 @@ impl SmbClient {
         S: AsRef<str>,
     {
         trace!("listing files at {}", path.as_ref());
-        let path = utils::str_to_cstring(self.uri(path))?;
-        let opendir_fn = self.get_fn(self.ctx()?, smbc_getFunctionOpendir)?;
-        let fd = opendir_fn(self.ctx()?, path.as_ptr());
-        if fd.is_null() {
-            error!("failed to open directory: returned a bad file descriptor");
-            return Err(SmbError::BadFileDescriptor);
-        }
-        let closedir_fn = self.get_fn(self.ctx()?, smbc_getFunctionClosedir)?;
         let mut entries = Vec::new();
-        let readdir_fn = self.get_fn(self.ctx()?, smbc_getFunctionReaddir)?;
-        loop {
-            let dirent = readdir_fn(self.ctx()?, fd);
-            if dirent.is_null() {
-                break;
-            }
-            unsafe {
-                match SmbDirent::try_from(*dirent) {
-                    Ok(dirent)
-                        if dirent.name() != "."
-                            && dirent.name() != ".."
-                            && !dirent.name().is_empty() =>
-                    {
-                        trace!("found dirent: {:?}", dirent);
-                        entries.push(dirent);
-                    }
-                    Ok(_) => {
-                        trace!("ignoring '..', '.' directories");
-                    }
-                    Err(e) => {
-                        error!("failed to decode directory entity {:?}: {}", dirent, e);
-                    }
-                }
+        for dirent in self.read_dir(path)? {
+            match dirent {
+                Ok(dirent) => entries.push(dirent),
+                Err(SmbError::Io(e)) => return Err(SmbError::Io(e)),
+                Err(e) => error!("failed to decode directory entity: {}", e),
             }
         }
         trace!("decoded {} dirents", entries.len());
-        // Close directory
-        let _ = closedir_fn(self.ctx()?, fd);
         Ok(entries)
     }
 
+    /// List content of directory at `path` as of `snapshot`
+    pub fn list_dir_at<S>(&self, path: S, snapshot: SmbSnapshot) -> SmbResult<Vec<SmbDirent>>
+    where
+        S: AsRef<str>,
+    {
+        self.list_dir(snapshot.path(path.as_ref()))
+    }
+
+    /// List the snapshots which cover the entry at `path`, sorted from the oldest to the newest.
+    ///
+    /// libsmbclient doesn't expose the snapshots enumeration request, so snapshots are found by their
+    /// `@GMT-YYYY.MM.DD-HH.MM.SS` names in the nearest `.snapshots` directory from `path` up to the root of the share,
+    /// which is where `vfs_shadow_copy2` keeps them by default; it must be visible in the share.
+    /// The entry may not exist in all of them, which [`SmbClient::stat_at`] tells.
+    ///
+    /// # Limitations
+    ///
+    /// - Only Samba servers keeping the snapshots in a visible directory (`shadow:snapdir`) are supported:
+    ///   Windows servers and other VSS-based servers don't expose them as a directory,
+    ///   so no snapshots are found there, as when there's no such directory
+    pub fn list_snapshots<S>(&self, path: S) -> SmbResult<Vec<SmbSnapshot>>
+    where
+        S: AsRef<str>,
+    {
+        trace!("listing snapshots of {}", path.as_ref());
+        let Some(snapdir) = self.find_snapdir(path.as_ref()) else {
+            debug!("no snapshots directory found for {}", path.as_ref());
+            return Ok(Vec::new());
+        };
+        let mut snapshots: Vec<SmbSnapshot> = self
+            .list_dirplus(snapdir)?
+            .iter()
+            .filter(|info| info.attributes().contains(SmbFileAttributes::DIRECTORY))
+            .filter_map(|info| SmbSnapshot::from_str(info.name()).ok())
+            .collect();
+        snapshots.sort();
+        Ok(snapshots)
+    }
+
+    /// Find the nearest snapshots directory from `path` up to the root of the share
+    fn find_snapdir(&self, path: &str) -> Option<String> {
+        let mut dir = path.trim_end_matches('/');
+        loop {
+            let snapdir = utils::join_path(dir, DEFAULT_SNAPDIR);
+            if self.ensure_is_dir(&snapdir, SmbError::NotSupported).is_ok() {
+                return Some(snapdir);
+            }
+            dir = &dir[..dir.rfind('/')?];
+        }
+    }
+
     /// List content of directory with metadata at 'path'
     pub fn list_dirplus<S>(&self, path: S) -> SmbResult<Vec<SmbDirentInfo>>
     where
@@ -297,6 +525,30 @@ impl SmbClient {
         Ok(entries)
     }
 
+    /// List content of directory at `path` with metadata and full stat of each entry.
+    ///
+    /// Entries are read in one pass with `readdirplus2`; where it is not available,
+    /// it falls back to [`SmbClient::list_dirplus`] and a [`SmbClient::stat`] for each entry.
+    pub fn list_dirplus2<S>(&self, path: S) -> SmbResult<Vec<SmbDirentStat>>
+    where
+        S: AsRef<str>,
+    {
+        trace!("listing files with stat at {}", path.as_ref());
+        match self.readdirplus2(path.as_ref()) {
+            Err(SmbError::NotSupported) => {
+                debug!("readdirplus2 is not supported; falling back to list_dirplus and stat");
+                self.list_dirplus(path.as_ref())?
+                    .into_iter()
+                    .map(|info| {
+                        let stat = self.stat(utils::join_path(path.as_ref(), info.name()))?;
+                        Ok(SmbDirentStat::new(info, stat))
+                    })
+                    .collect()
+            }
+            res => res,
+        }
+    }
+
     /// Make directory at `p` with provided `mode`
     pub fn mkdir<S>(&self, p: S, mode: SmbMode) -> SmbResult<()>
     where
@@ -319,6 +571,81 @@ impl SmbClient {
         utils::to_result_with_ioerror((), rmdir_fn(self.ctx()?, p.as_ptr()))
     }
 
+    /// Recursively create a directory and all of its parent components if they are missing.
+    ///
+    /// Directories which already exist are not considered an error
+    pub fn create_dir_all<S>(&self, p: S, mode: SmbMode) -> SmbResult<()>
+    where
+        S: AsRef<str>,
+    {
+        let p = p.as_ref().trim_end_matches('/');
+        if p.is_empty() {
+            return Ok(());
+        }
+        trace!("creating directory at {} with parents", p);
+        match self.mkdir(p, mode) {
+            Ok(()) => return Ok(()),
+            Err(e) if e.raw_os_error() == Some(libc::ENOENT) => {
+                if let Some((parent, _)) = p.rsplit_once('/') {
+                    self.create_dir_all(parent, mode)?;
+                }
+            }
+            Err(e) if e.raw_os_error() == Some(libc::EEXIST) => {
+                return self.ensure_is_dir(p, e);
+            }
+            Err(e) => return Err(e),
+        }
+        // parents have been created; create directory
+        match self.mkdir(p, mode) {
+            Err(e) if e.raw_os_error() == Some(libc::EEXIST) => self.ensure_is_dir(p, e),
+            res => res,
+        }
+    }
+
+    /// Remove the directory at `p` after removing all of its content.
+    ///
+    /// Read-only entries are made writable before being removed.
+    /// The removal stops at the first failure, which reports the path of the entry which couldn't be removed
+    pub fn remove_dir_all<S>(&self, p: S) -> SmbResult<()>
+    where
+        S: AsRef<str>,
+    {
+        self.remove_dir_all_with(p, SmbRemoveDirOptions::default())
+    }
+
+    /// Remove the directory at `p` after removing all of its content, with the provided `options`.
+    ///
+    /// Errors report the path of the entry which couldn't be removed;
+    /// if `continue_on_error` is set, all the errors are collected into [`SmbError::Multiple`]
+    pub fn remove_dir_all_with<S>(&self, p: S, options: SmbRemoveDirOptions) -> SmbResult<()>
+    where
+        S: AsRef<str>,
+    {
+        trace!("removing directory at {} with its content", p.as_ref());
+        let p = p.as_ref().trim_end_matches('/');
+        let stat = self.stat(p).map_err(|e| SmbError::with_path(e, p))?;
+        if !stat.mode.is_dir() {
+            let error = std::io::Error::from_raw_os_error(libc::ENOTDIR);
+            return Err(SmbError::with_path(error.into(), p));
+        }
+        // stat doesn't carry the DOS attributes
+        let attributes = self
+            .get_attributes(p)
+            .unwrap_or(SmbFileAttributes::DIRECTORY);
+        let mut errors = Vec::new();
+        let res = self.remove_dir_tree(
+            p,
+            attributes | SmbFileAttributes::DIRECTORY,
+            &options,
+            &mut errors,
+        );
+        match (res, errors.len()) {
+            (Err(e), _) => Err(e),
+            (Ok(()), 0) => Ok(()),
+            (Ok(()), _) => Err(SmbError::Multiple(errors)),
+        }
+    }
+
     /// Stat filesystem at `p` and return its metadata
     pub fn statvfs<S>(&self, p: S) -> SmbResult<SmbStatVfs>
     where
@@ -344,17 +671,28 @@ impl SmbClient {
         S: AsRef<str>,
     {
         trace!("Stating file at {}", p.as_ref());
-        let p = utils::str_to_cstring(self.uri(p))?;
-        unsafe {
-            let mut st: libc::stat = mem::zeroed();
-            let stat_fn = self.get_fn(self.ctx()?, smbc_getFunctionStat)?;
-            if stat_fn(self.ctx()?, p.as_ptr(), &mut st) < 0 {
-                error!("failed to stat file: {}", utils::last_os_error());
-                Err(utils::last_os_error())
-            } else {
-                Ok(SmbStat::from(st))
+        let uri = utils::str_to_cstring(self.uri(p.as_ref()))?;
+        let mut st: libc::stat = unsafe { mem::zeroed() };
+        let stat_fn = self.get_fn(self.ctx()?, smbc_getFunctionStat)?;
+        if stat_fn(self.ctx()?, uri.as_ptr(), &mut st) < 0 {
+            error!("failed to stat file: {}", utils::last_os_error());
+            return Err(utils::last_os_error());
+        }
+        let mut stat = SmbStat::from(st);
+        // stat doesn't carry the birth time; the create time xattr only exists with `full_time_names`
+        if unsafe { smbc_getOptionFullTimeNames(self.ctx()?) } != 0 {
+            match self.get_birth_time(p.as_ref()) {
+                Ok(created) => stat.created = created,
+                Err(e) => debug!("could not get birth time for {}: {}", p.as_ref(), e),
             }
         }
+        Ok(stat)
+    }
+
+    /// Get the birth time of the entry at `p` from the create time xattr
+    fn get_birth_time(&self, p: &str) -> SmbResult<SystemTime> {
+        let value = self.get_xattr(p, XATTR_DOS_ATTR_CREATE_TIME)?;
+        utils::parse_xattr_time(&value)
     }
 
     /// Change file mode for file at `p`
@@ -368,6 +706,76 @@ impl SmbClient {
         utils::to_result_with_ioerror((), chmod_fn(self.ctx()?, p.as_ptr(), mode.into()))
     }
 
+    /// List the jobs in the print queue `print_queue`
+    ///
+    /// Print jobs are managed with RAP, which servers only support over SMB1
+    pub fn list_print_jobs<S>(&self, print_queue: S) -> SmbResult<Vec<SmbPrintJobInfo>>
+    where
+        S: AsRef<str>,
+    {
+        trace!("listing print jobs in {} queue", print_queue.as_ref());
+        let print_queue = utils::str_to_cstring(self.uri(print_queue))?;
+        let list_print_jobs_fn = self.get_fn(self.ctx()?, smbc_getFunctionListPrintJobs)?;
+        PRINT_JOBS.with(|jobs| jobs.borrow_mut().clear());
+        let res = list_print_jobs_fn(
+            self.ctx()?,
+            print_queue.as_ptr(),
+            Some(Self::list_print_job_wrapper),
+        );
+        let jobs = PRINT_JOBS.with(|jobs| jobs.take());
+        utils::to_result_with_ioerror(jobs, res)
+    }
+
+    /// Cancel the job with `id` in the print queue `print_queue`
+    ///
+    /// Print jobs are managed with RAP, which servers only support over SMB1
+    pub fn cancel_print_job<S>(&self, print_queue: S, id: u16) -> SmbResult<()>
+    where
+        S: AsRef<str>,
+    {
+        trace!("cancelling job {} in {} queue", id, print_queue.as_ref());
+        let print_queue = utils::str_to_cstring(self.uri(print_queue))?;
+        let unlink_print_job_fn = self.get_fn(self.ctx()?, smbc_getFunctionUnlinkPrintJob)?;
+        utils::to_result_with_ioerror(
+            (),
+            unlink_print_job_fn(self.ctx()?, print_queue.as_ptr(), id as c_int),
+        )
+    }
+
+    /// Get DOS attributes of file at `p`
+    pub fn get_attributes<S>(&self, p: S) -> SmbResult<SmbFileAttributes>
+    where
+        S: AsRef<str>,
+    {
+        trace!("getting DOS attributes for {}", p.as_ref());
+        SmbFileAttributes::from_xattr(&self.get_xattr(p.as_ref(), XATTR_DOS_ATTR_MODE)?)
+    }
+
+    /// Set DOS attributes of file at `p`.
+    ///
+    /// An empty set clears all the attributes, and is sent as `NORMAL`
+    pub fn set_attributes<S>(&self, p: S, attributes: SmbFileAttributes) -> SmbResult<()>
+    where
+        S: AsRef<str>,
+    {
+        trace!(
+            "setting DOS attributes for {} to {:?}",
+            p.as_ref(),
+            attributes
+        );
+        // no attributes mean "don't change" to the server, so clear them explicitly
+        let attributes = match attributes.is_empty() {
+            true => SmbFileAttributes::NORMAL,
+            false => attributes,
+        };
+        self.set_xattr(
+            p.as_ref(),
+            XATTR_DOS_ATTR_MODE,
+            &attributes.to_xattr(),
+            SmbXattrFlag::Any,
+        )
+    }
+
     /// Print file at `p` using the `print_queue`
     pub fn print<S>(&self, p: S, print_queue: S) -> SmbResult<()>
     where
@@ -383,8 +791,338 @@ impl SmbClient {
         )
     }
 
+    /// Get the value of the extended attribute `name` for the file at `p`.
+    ///
+    /// libsmbclient exposes its metadata through the `system.*` namespace,
+    /// e.g. `system.dos_attr.mode` or `system.nt_sec_desc.*`
+    pub fn get_xattr<S>(&self, p: S, name: S) -> SmbResult<String>
+    where
+        S: AsRef<str>,
+    {
+        trace!("getting xattr {} for {}", name.as_ref(), p.as_ref());
+        let path = utils::str_to_cstring(self.uri(p))?;
+        let c_name = utils::str_to_cstring(name.as_ref())?;
+        let getxattr_fn = self.get_fn(self.ctx()?, smbc_getFunctionGetxattr)?;
+        let ctx = self.ctx()?;
+        let buf = Self::read_xattr_buffer(name.as_ref(), false, |buf, size| {
+            getxattr_fn(
+                ctx,
+                path.as_ptr(),
+                c_name.as_ptr(),
+                buf as *const c_void,
+                size,
+            )
+        })?;
+        // value is a nul-terminated string
+        let len = buf.iter().position(|x| *x == 0).unwrap_or(buf.len());
+        String::from_utf8(buf[..len].to_vec()).map_err(|_| SmbError::BadValue)
+    }
+
+    /// Set the extended attribute `name` for the file at `p` to `value`.
+    ///
+    /// `flag` describes whether the attribute must or must not already exist
+    pub fn set_xattr<S>(&self, p: S, name: S, value: S, flag: SmbXattrFlag) -> SmbResult<()>
+    where
+        S: AsRef<str>,
+    {
+        trace!(
+            "setting xattr {} for {} to {} ({:?})",
+            name.as_ref(),
+            p.as_ref(),
+            value.as_ref(),
+            flag
+        );
+        let path = utils::str_to_cstring(self.uri(p))?;
+        let c_name = utils::str_to_cstring(name.as_ref())?;
+        let c_value = utils::str_to_cstring(value.as_ref())?;
+        let setxattr_fn = self.get_fn(self.ctx()?, smbc_getFunctionSetxattr)?;
+        if setxattr_fn(
+            self.ctx()?,
+            path.as_ptr(),
+            c_name.as_ptr(),
+            c_value.as_ptr() as *const c_void,
+            value.as_ref().len(),
+            flag.into(),
+        ) < 0
+        {
+            Err(Self::xattr_error(name.as_ref()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// List the names of the extended attributes supported for the file at `p`
+    pub fn list_xattr<S>(&self, p: S) -> SmbResult<Vec<String>>
+    where
+        S: AsRef<str>,
+    {
+        trace!("listing xattrs for {}", p.as_ref());
+        let path = utils::str_to_cstring(self.uri(p))?;
+        let listxattr_fn = self.get_fn(self.ctx()?, smbc_getFunctionListxattr)?;
+        let ctx = self.ctx()?;
+        let buf = Self::read_xattr_buffer("", true, |buf, size| {
+            listxattr_fn(ctx, path.as_ptr(), buf, size)
+        })?;
+        // names are separated by nul characters
+        buf.split(|x| *x == 0)
+            .filter(|x| !x.is_empty())
+            .map(|x| String::from_utf8(x.to_vec()).map_err(|_| SmbError::BadValue))
+            .collect()
+    }
+
+    /// Remove the extended attribute `name` from the file at `p`
+    pub fn remove_xattr<S>(&self, p: S, name: S) -> SmbResult<()>
+    where
+        S: AsRef<str>,
+    {
+        trace!("removing xattr {} from {}", name.as_ref(), p.as_ref());
+        let path = utils::str_to_cstring(self.uri(p))?;
+        let c_name = utils::str_to_cstring(name.as_ref())?;
+        let removexattr_fn = self.get_fn(self.ctx()?, smbc_getFunctionRemovexattr)?;
+        if removexattr_fn(self.ctx()?, path.as_ptr(), c_name.as_ptr()) < 0 {
+            Err(Self::xattr_error(name.as_ref()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Get the NT security descriptor of the file at `p`.
+    ///
+    /// If `resolve_names` is `true`, SIDs are resolved to account names by the server
+    pub fn get_security_descriptor<S>(
+        &self,
+        p: S,
+        resolve_names: bool,
+    ) -> SmbResult<SmbSecurityDescriptor>
+    where
+        S: AsRef<str>,
+    {
+        trace!("getting security descriptor for {}", p.as_ref());
+        let name = match resolve_names {
+            true => XATTR_NT_SEC_DESC_NAMES,
+            false => XATTR_NT_SEC_DESC,
+        };
+        SmbSecurityDescriptor::from_str(&self.get_xattr(p.as_ref(), name)?)
+    }
+
+    /// Set the NT security descriptor of the file at `p`.
+    ///
+    /// The DACL is replaced by the ACL of `sd`; owner and group are changed only if set
+    pub fn set_security_descriptor<S>(&self, p: S, sd: &SmbSecurityDescriptor) -> SmbResult<()>
+    where
+        S: AsRef<str>,
+    {
+        trace!("setting security descriptor for {} to {}", p.as_ref(), sd);
+        let name = match sd.has_names() {
+            true => XATTR_NT_SEC_DESC_NAMES,
+            false => XATTR_NT_SEC_DESC,
+        };
+        self.set_xattr(p.as_ref(), name, &sd.to_string(), SmbXattrFlag::Any)
+    }
+
+    /// Add `ace` to the DACL of the file at `p`
+    pub fn add_ace<S>(&self, p: S, ace: SmbAce) -> SmbResult<()>
+    where
+        S: AsRef<str>,
+    {
+        trace!("adding ACE {} to {}", ace, p.as_ref());
+        let mut sd = self.get_dacl(p.as_ref(), ace.trustee.is_name())?;
+        sd.add_ace(ace);
+        self.set_security_descriptor(p, &sd)
+    }
+
+    /// Remove all the entries equal to `ace` from the DACL of the file at `p`.
+    ///
+    /// Returns whether any entry has been removed
+    pub fn remove_ace<S>(&self, p: S, ace: &SmbAce) -> SmbResult<bool>
+    where
+        S: AsRef<str>,
+    {
+        trace!("removing ACE {} from {}", ace, p.as_ref());
+        let mut sd = self.get_dacl(p.as_ref(), ace.trustee.is_name())?;
+        if !sd.remove_ace(ace) {
+            return Ok(false);
+        }
+        self.set_security_descriptor(p, &sd)?;
+        Ok(true)
+    }
+
     // -- internal private
 
+    /// Get the security descriptor of the file at `p` without owner and group,
+    /// so that setting it back only changes the DACL
+    fn get_dacl(&self, p: &str, resolve_names: bool) -> SmbResult<SmbSecurityDescriptor> {
+        let sd = self.get_security_descriptor(p, resolve_names)?;
+        Ok(SmbSecurityDescriptor {
+            owner: None,
+            group: None,
+            ..sd
+        })
+    }
+
+    /// Call `read` (a `getxattr` or `listxattr` function) with a buffer large enough to hold the result.
+    ///
+    /// The required size is probed calling `read` with a zero-sized buffer first;
+    /// then the buffer is grown as long as `read` fails with `ERANGE`.
+    /// If `returns_len` the buffer is truncated to the length returned by `read` (`listxattr`);
+    /// otherwise `read` returns 0 on success (`getxattr`) and the whole buffer is returned
+    fn read_xattr_buffer<F>(name: &str, returns_len: bool, mut read: F) -> SmbResult<Vec<u8>>
+    where
+        F: FnMut(*mut c_char, usize) -> c_int,
+    {
+        let mut size = match read(ptr::null_mut(), 0) {
+            ret if ret > 0 => ret as usize + 1,
+            ret if ret == 0 || utils::last_errno() == libc::ERANGE => XATTR_BUFFER_SIZE,
+            _ => return Err(Self::xattr_error(name)),
+        };
+        loop {
+            let mut buf = vec![0u8; size];
+            let ret = read(buf.as_mut_ptr() as *mut c_char, size);
+            if ret >= 0 {
+                if returns_len {
+                    buf.truncate((ret as usize).min(size));
+                }
+                return Ok(buf);
+            }
+            if utils::last_errno() != libc::ERANGE || size >= XATTR_BUFFER_MAX_SIZE {
+                return Err(Self::xattr_error(name));
+            }
+            size *= 2;
+        }
+    }
+
+    /// Convert the last os error returned by an xattr function to a `SmbError`
+    fn xattr_error(name: &str) -> SmbError {
+        let err = std::io::Error::last_os_error();
+        error!("xattr operation on {} failed: {}", name, err);
+        match err.raw_os_error() {
+            Some(ENOATTR) => SmbError::NoSuchXattr(name.to_string()),
+            Some(libc::EEXIST) => SmbError::XattrExists(name.to_string()),
+            Some(libc::ENOTSUP) => SmbError::NotSupported,
+            _ => SmbError::Io(err),
+        }
+    }
+
+    /// List directory at `path` with `readdirplus2`.
+    ///
+    /// Returns `NotSupported` if `readdirplus2` is not available
+    fn readdirplus2(&self, path: &str) -> SmbResult<Vec<SmbDirentStat>> {
+        let readdirplus2_fn = unsafe { symbols::get_function_readdirplus2(self.ctx()?) }
+            .ok_or(SmbError::NotSupported)?;
+        let uri = utils::str_to_cstring(self.uri(path))?;
+        let opendir_fn = self.get_fn(self.ctx()?, smbc_getFunctionOpendir)?;
+        let fd = opendir_fn(self.ctx()?, uri.as_ptr());
+        if fd.is_null() {
+            error!("failed to open directory: returned a bad file descriptor");
+            return Err(SmbError::BadFileDescriptor);
+        }
+        let closedir_fn = self.get_fn(self.ctx()?, smbc_getFunctionClosedir)?;
+        let mut entries = Vec::new();
+        let mut result = Ok(());
+        loop {
+            let mut st: libc::stat = unsafe { mem::zeroed() };
+            // a null entry is both the end of the directory and an error; only an error sets errno
+            utils::clear_errno();
+            let direntplus = readdirplus2_fn(self.ctx()?, fd, &mut st);
+            if direntplus.is_null() {
+                match utils::last_errno() {
+                    0 => {}
+                    libc::ENOSYS | libc::ENOTSUP if entries.is_empty() => {
+                        result = Err(SmbError::NotSupported);
+                    }
+                    errno => {
+                        error!("failed to read directory: {}", utils::last_os_error());
+                        result = Err(std::io::Error::from_raw_os_error(errno).into());
+                    }
+                }
+                break;
+            }
+            match SmbDirentInfo::try_from(unsafe { *direntplus }) {
+                Ok(info)
+                    if info.name() != "." && info.name() != ".." && !info.name().is_empty() =>
+                {
+                    trace!("found direntplus: {:?}", info);
+                    entries.push(SmbDirentStat::new(info, SmbStat::from(st)));
+                }
+                Ok(_) => {
+                    trace!("ignoring '..', '.' directories");
+                }
+                Err(e) => {
+                    error!("failed to decode directory entity with stat: {}", e);
+                }
+            }
+        }
+        trace!("decoded {} direntpluses with stat", entries.len());
+        // Close directory
+        let _ = closedir_fn(self.ctx()?, fd);
+        result.map(|_| entries)
+    }
+
+    /// Check whether `p` is an existing directory; if it isn't return `err`
+    fn ensure_is_dir(&self, p: &str, err: SmbError) -> SmbResult<()> {
+        match self.stat(p) {
+            Ok(stat) if stat.mode.is_dir() => Ok(()),
+            _ => Err(err),
+        }
+    }
+
+    /// Remove the entry at `p` with DOS `attributes`; if it's a directory, its content is removed first.
+    ///
+    /// If `continue_on_error` is set in `options`, errors are pushed to `errors` and `Ok` is returned
+    fn remove_dir_tree(
+        &self,
+        p: &str,
+        attributes: SmbFileAttributes,
+        options: &SmbRemoveDirOptions,
+        errors: &mut Vec<SmbError>,
+    ) -> SmbResult<()> {
+        let fail = |error: SmbError, errors: &mut Vec<SmbError>| {
+            let error = SmbError::with_path(error, p);
+            error!("failed to remove entry: {}", error);
+            if options.continue_on_error {
+                errors.push(error);
+                Ok(())
+            } else {
+                Err(error)
+            }
+        };
+        if options.clear_readonly && attributes.contains(SmbFileAttributes::READONLY) {
+            trace!("clearing read-only attribute of {}", p);
+            if let Err(e) = self.set_attributes(p, attributes - SmbFileAttributes::READONLY) {
+                return fail(e, errors);
+            }
+        }
+        let is_dir = attributes.contains(SmbFileAttributes::DIRECTORY);
+        // reparse points (e.g. junctions) are removed without following them
+        if is_dir && !attributes.contains(SmbFileAttributes::REPARSE_POINT) {
+            let entries = match self.list_dirplus(p) {
+                Ok(entries) => entries,
+                Err(e) => return fail(e, errors),
+            };
+            let errors_before = errors.len();
+            for entry in entries {
+                self.remove_dir_tree(
+                    &utils::join_path(p, entry.name()),
+                    entry.attributes(),
+                    options,
+                    errors,
+                )?;
+            }
+            if errors.len() != errors_before {
+                // directory can't be empty
+                return Ok(());
+            }
+        }
+        let res = match is_dir {
+            true => self.rmdir(p),
+            false => self.unlink(p),
+        };
+        match res {
+            Ok(()) => Ok(()),
+            Err(e) => fail(e, errors),
+        }
+    }
+
     /// Build connection uri
     fn build_uri(server: &str, share: &str) -> String {
         format!(
@@ -417,26 +1155,240 @@ impl SmbClient {
     }
 
     /// Setup options in the context
-    unsafe fn setup_options(ctx: *mut SMBCCTX, options: SmbOptions) {
+    unsafe fn setup_options(ctx: *mut SMBCCTX, options: SmbOptions) -> SmbResult<()> {
         smbc_setOptionBrowseMaxLmbCount(ctx, options.browser_max_lmb_count);
-        smbc_setOptionCaseSensitive(ctx, options.case_sensitive as i32);
+        smbc_setOptionCaseSensitive(
+            ctx,
+            (options.case_sensitive || options.posix_extensions) as i32,
+        );
         smbc_setOptionDebugToStderr(ctx, 0);
         smbc_setOptionFallbackAfterKerberos(ctx, options.fallback_after_kerberos as i32);
+        smbc_setOptionFullTimeNames(ctx, options.full_time_names as i32);
         smbc_setOptionNoAutoAnonymousLogin(ctx, options.no_auto_anonymous_login as i32);
         smbc_setOptionOneSharePerServer(ctx, options.one_share_per_server as i32);
         smbc_setOptionOpenShareMode(ctx, options.open_share_mode.into());
+        if let Some(port) = options.port {
+            smbc_setPort(ctx, port);
+        }
+        if symbols::set_option_posix_extensions(ctx, options.posix_extensions as i32).is_none()
+            && options.posix_extensions
+        {
+            return Err(SmbError::BadOption(
+                "POSIX extensions are not supported by libsmbclient".to_string(),
+            ));
+        }
         smbc_setOptionSmbEncryptionLevel(ctx, options.encryption_level.into());
         smbc_setOptionUrlEncodeReaddirEntries(ctx, options.url_encode_readdir_entries as i32);
         smbc_setOptionUseCCache(ctx, options.use_ccache as i32);
         smbc_setOptionUseKerberos(ctx, options.use_kerberos as i32);
-        #[cfg(feature = "debug")]
-        smbc_setOptionDebugToStderr(ctx, 1 as i32);
-        #[cfg(feature = "debug")]
-        smbc_setDebug(ctx, 10);
+        smbc_setLogCallback(ctx, ptr::null_mut(), Some(Self::log_wrapper));
+        smbc_setDebug(ctx, options.debug_level);
+        Self::setup_server_cache(ctx, &options)?;
+        if let Some(path) = options.config_file.as_deref() {
+            Self::load_configuration_file(ctx, path)?;
+        }
+        // loaded after the file, so that the options override it
+        if let Some(conf) = options.configuration() {
+            Self::load_configuration(ctx, &conf)?;
+        }
+        if options.min_protocol.is_some() || options.max_protocol.is_some() {
+            Self::setup_protocols(ctx, options.min_protocol, options.max_protocol)?;
+        }
+        Ok(())
     }
 
-    /// Auth wrapper passed to `SMBCCTX` to authenticate requests to SMB servers.
-    extern "C" fn auth_wrapper(
+    /// Wrap the server cache functions of the context, to track the cached servers and enforce the cache policy
+    unsafe fn setup_server_cache(ctx: *mut SMBCCTX, options: &SmbOptions) -> SmbResult<()> {
+        let mut cache = SERVER_CACHE.lock().map_err(|_| SmbError::Mutex)?;
+        *cache = ServerCache::new(
+            options.connection_idle_timeout,
+            options.max_cached_connections,
+        );
+        cache.add_fn = smbc_getFunctionAddCachedServer(ctx);
+        cache.get_fn = smbc_getFunctionGetCachedServer(ctx);
+        cache.remove_fn = smbc_getFunctionRemoveCachedServer(ctx);
+        smbc_setFunctionAddCachedServer(ctx, Some(Self::add_cached_srv_wrapper));
+        smbc_setFunctionGetCachedServer(ctx, Some(Self::get_cached_srv_wrapper));
+        smbc_setFunctionRemoveCachedServer(ctx, Some(Self::remove_cached_srv_wrapper));
+        Ok(())
+    }
+
+    /// Load the smb.conf `content` in the context
+    unsafe fn load_configuration(ctx: *mut SMBCCTX, content: &str) -> SmbResult<()> {
+        // the file is created exclusively, readable only by the owner, in a private directory
+        let dir = tempfile::Builder::new().prefix("pavao-").tempdir()?;
+        let mut file = tempfile::Builder::new()
+            .suffix(".conf")
+            .tempfile_in(dir.path())?;
+        file.write_all(content.as_bytes())?;
+        file.flush()?;
+        Self::load_configuration_file(ctx, file.path())
+    }
+
+    /// Load the smb.conf at `path` in the context, on top of the configuration already loaded
+    unsafe fn load_configuration_file(ctx: *mut SMBCCTX, path: &Path) -> SmbResult<()> {
+        trace!("loading configuration from {}", path.display());
+        // unknown parameters are only reported in the logs
+        CONFIG_ERRORS.with(|errors| *errors.borrow_mut() = Some(Vec::new()));
+        let rc = utils::str_to_cstring(path.to_string_lossy())
+            .map(|file| smbc_setConfiguration(ctx, file.as_ptr()));
+        let errors = CONFIG_ERRORS
+            .with(|errors| errors.borrow_mut().take())
+            .unwrap_or_default();
+        match rc? {
+            0 if errors.is_empty() => Ok(()),
+            0 => Err(SmbError::BadOption(errors.join("; "))),
+            _ => Err(SmbError::BadOption(
+                "configuration rejected by libsmbclient".to_string(),
+            )),
+        }
+    }
+
+    /// Set the range of protocol dialects which can be negotiated; `None` leaves the bound unchanged
+    unsafe fn setup_protocols(
+        ctx: *mut SMBCCTX,
+        min: Option<SmbProtocol>,
+        max: Option<SmbProtocol>,
+    ) -> SmbResult<()> {
+        trace!("setting protocols range to {:?}..{:?}", min, max);
+        let min_proto = min.map(|p| utils::str_to_cstring(p.as_str())).transpose()?;
+        let max_proto = max.map(|p| utils::str_to_cstring(p.as_str())).transpose()?;
+        let rc = smbc_setOptionProtocols(
+            ctx,
+            min_proto
+                .as_ref()
+                .map(|p| p.as_ptr())
+                .unwrap_or(ptr::null()),
+            max_proto
+                .as_ref()
+                .map(|p| p.as_ptr())
+                .unwrap_or(ptr::null()),
+        );
+        match rc {
+            0 => Err(SmbError::BadOption(format!(
+                "protocols range {:?}..{:?} rejected by libsmbclient",
+                min, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Log callback passed to `SMBCCTX` to forward libsmbclient messages to `log`, and to `tracing` with the `tracing` feature
+    extern "C" fn log_wrapper(_private: *mut c_void, level: c_int, msg: *const c_char) {
+        if msg.is_null() {
+            return;
+        }
+        let msg = unsafe { utils::cstr(msg) };
+        let msg = msg.trim_end();
+        if msg.is_empty() {
+            return;
+        }
+        CONFIG_ERRORS.with(|errors| {
+            if let Some(errors) = errors.borrow_mut().as_mut() {
+                if msg.to_lowercase().contains("unknown parameter") {
+                    errors.push(msg.to_string());
+                }
+            }
+        });
+        let level = utils::debug_level_to_log_level(level);
+        log!(target: "libsmbclient", level, "{}", msg);
+        #[cfg(feature = "tracing")]
+        match level {
+            log::Level::Error => tracing::error!(target: "libsmbclient", "{}", msg),
+            log::Level::Warn => tracing::warn!(target: "libsmbclient", "{}", msg),
+            log::Level::Info => tracing::info!(target: "libsmbclient", "{}", msg),
+            log::Level::Debug => tracing::debug!(target: "libsmbclient", "{}", msg),
+            log::Level::Trace => tracing::trace!(target: "libsmbclient", "{}", msg),
+        }
+    }
+
+    /// Add cached server wrapper passed to `SMBCCTX`; closes the least recently used servers exceeding the cache size
+    extern "C" fn add_cached_srv_wrapper(
+        ctx: *mut SMBCCTX,
+        srv: *mut SMBCSRV,
+        server: *const c_char,
+        share: *const c_char,
+        workgroup: *const c_char,
+        username: *const c_char,
+    ) -> c_int {
+        // never call libsmbclient while holding the lock, since it calls back the wrappers
+        let Some(add) = SERVER_CACHE.lock().ok().and_then(|cache| cache.add_fn) else {
+            return 1;
+        };
+        let ret = add(ctx, srv, server, share, workgroup, username);
+        if ret == 0 {
+            let excess = match SERVER_CACHE.lock() {
+                Ok(mut cache) => {
+                    cache.added(srv as usize, Instant::now());
+                    cache.excess()
+                }
+                Err(_) => Vec::new(),
+            };
+            Self::evict_servers(ctx, excess);
+        }
+        ret
+    }
+
+    /// Get cached server wrapper passed to `SMBCCTX`; closes the servers idle for too long before the lookup
+    extern "C" fn get_cached_srv_wrapper(
+        ctx: *mut SMBCCTX,
+        server: *const c_char,
+        share: *const c_char,
+        workgroup: *const c_char,
+        username: *const c_char,
+    ) -> *mut SMBCSRV {
+        let (get, expired) = match SERVER_CACHE.lock() {
+            Ok(cache) => (cache.get_fn, cache.expired(Instant::now())),
+            Err(_) => return ptr::null_mut(),
+        };
+        Self::evict_servers(ctx, expired);
+        let Some(get) = get else {
+            return ptr::null_mut();
+        };
+        let srv = get(ctx, server, share, workgroup, username);
+        if let Ok(mut cache) = SERVER_CACHE.lock() {
+            match srv.is_null() {
+                true => cache.missed(),
+                false => cache.hit(srv as usize, Instant::now()),
+            }
+        }
+        srv
+    }
+
+    /// Remove cached server wrapper passed to `SMBCCTX`
+    extern "C" fn remove_cached_srv_wrapper(ctx: *mut SMBCCTX, srv: *mut SMBCSRV) -> c_int {
+        let Some(remove) = SERVER_CACHE.lock().ok().and_then(|cache| cache.remove_fn) else {
+            return 1;
+        };
+        let ret = remove(ctx, srv);
+        if ret == 0 {
+            if let Ok(mut cache) = SERVER_CACHE.lock() {
+                cache.removed(srv as usize);
+            }
+        }
+        ret
+    }
+
+    /// Close the cached `servers` which have no open files or directories
+    fn evict_servers(ctx: *mut SMBCCTX, servers: Vec<usize>) {
+        if servers.is_empty() {
+            return;
+        }
+        let Some(remove_unused) = (unsafe { smbc_getFunctionRemoveUnusedServer(ctx) }) else {
+            return;
+        };
+        for srv in servers {
+            if remove_unused(ctx, srv as *mut SMBCSRV) == 0 {
+                debug!("closed cached server {:#x}", srv);
+                if let Ok(mut cache) = SERVER_CACHE.lock() {
+                    cache.evicted();
+                }
+            }
+        }
+    }
+
+    /// Auth wrapper passed to `SMBCCTX` to authenticate requests to SMB servers.
+    extern "C" fn auth_wrapper(
         ctx: *mut SMBCCTX,
         srv: *const c_char,
         shr: *const c_char,
@@ -462,6 +1414,84 @@ impl SmbClient {
         }
     }
 
+    /// Open the directory at `path`
+    fn opendir<P: AsRef<str>>(&self, path: P) -> SmbResult<*mut SMBCFILE> {
+        self.opendir_uri(self.uri(path))
+    }
+
+    /// Open the directory at the absolute `uri`
+    fn opendir_uri(&self, uri: String) -> SmbResult<*mut SMBCFILE> {
+        let path = utils::str_to_cstring(uri)?;
+        let opendir_fn = self.get_fn(self.ctx()?, smbc_getFunctionOpendir)?;
+        let fd = opendir_fn(self.ctx()?, path.as_ptr());
+        if fd.is_null() {
+            error!("failed to open directory: returned a bad file descriptor");
+            return Err(SmbError::BadFileDescriptor);
+        }
+        Ok(fd)
+    }
+
+    /// Set the access and modification times and the DOS `attributes` to the entry at `path`.
+    ///
+    /// Metadata are set on a best-effort basis, since the server may not support them
+    fn preserve_metadata(
+        &self,
+        path: &str,
+        accessed: SystemTime,
+        modified: SystemTime,
+        attributes: SmbFileAttributes,
+    ) {
+        if let Err(e) = self.utimes(path, accessed, modified) {
+            warn!("failed to preserve times of {}: {}", path, e);
+        }
+        if let Err(e) = self.set_attributes(path, attributes) {
+            warn!("failed to preserve attributes of {}: {}", path, e);
+        }
+    }
+
+    /// Set access and modification times of the entry at `path`
+    fn utimes(&self, path: &str, accessed: SystemTime, modified: SystemTime) -> SmbResult<()> {
+        let uri = utils::str_to_cstring(self.uri(path))?;
+        let utimes_fn = self.get_fn(self.ctx()?, smbc_getFunctionUtimes)?;
+        let mut times = [
+            utils::system_time_to_timeval(accessed),
+            utils::system_time_to_timeval(modified),
+        ];
+        utils::to_result_with_ioerror((), utimes_fn(self.ctx()?, uri.as_ptr(), times.as_mut_ptr()))
+    }
+
+    /// Returns whether `err` means that the destination of a rename exists
+    fn is_already_existing(err: &SmbError) -> bool {
+        err.raw_os_error()
+            .is_some_and(|errno| [libc::EEXIST, libc::ENOTEMPTY].contains(&errno))
+    }
+
+    /// Returns whether `err` means that a server-side operation (copy or rename) is not available
+    fn is_unsupported(err: &SmbError) -> bool {
+        // ENOTSUP and EOPNOTSUPP are the same value only on some platforms
+        err.raw_os_error().is_some_and(|errno| {
+            [libc::ENOTSUP, libc::EOPNOTSUPP, libc::ENOSYS, libc::EXDEV].contains(&errno)
+        })
+    }
+
+    /// Callback passed to `splice` to report the copy progress
+    extern "C" fn splice_wrapper(n: off_t, private: *mut c_void) -> c_int {
+        if let Some(state) = unsafe { (private as *mut SpliceState).as_mut() } {
+            (state.progress)(n as u64, state.total);
+        }
+        // keep copying
+        1
+    }
+
+    /// Callback passed to `list_print_jobs` to collect the jobs in the queue
+    extern "C" fn list_print_job_wrapper(info: *mut print_job_info) {
+        if let Some(info) = unsafe { info.as_ref() } {
+            let job = SmbPrintJobInfo::from(info);
+            trace!("found print job: {:?}", job);
+            PRINT_JOBS.with(|jobs| jobs.borrow_mut().push(job));
+        }
+    }
+
     fn auth_service_uuid(ctx: *mut SMBCCTX) -> String {
         format!("{:?}", ctx)
     }
@@ -496,6 +1526,618 @@ impl<'a> SmbClient {
             Ok(SmbFile::new(self, fd))
         }
     }
+
+    /// Open the file at `path` as of `snapshot` with provided options.
+    ///
+    /// Snapshots are read-only, so the file can only be opened for reading
+    pub fn open_with_at<P: AsRef<str>>(
+        &'a self,
+        path: P,
+        snapshot: SmbSnapshot,
+        options: SmbOpenOptions,
+    ) -> SmbResult<SmbFile<'a>> {
+        self.open_with(snapshot.path(path.as_ref()), options)
+    }
+
+    /// Open the alternate data stream `name` of the file at `path` with provided options.
+    ///
+    /// This is the same as calling [`SmbClient::open_with`] with `path:name`;
+    /// with `create` the stream is created if it doesn't exist, but the file must exist
+    pub fn open_stream_with<P: AsRef<str>>(
+        &'a self,
+        path: P,
+        name: P,
+        options: SmbOpenOptions,
+    ) -> SmbResult<SmbFile<'a>> {
+        self.open_with(stream_path(path.as_ref(), name.as_ref())?, options)
+    }
+
+    /// Open the directory at `path` and return a lazy iterator over its entries.
+    ///
+    /// Unlike [`SmbClient::list_dir`], entries are read while iterating,
+    /// so the listing can be stopped early or paused and resumed with [`SmbReadDir::tell`] and [`SmbReadDir::seek`]
+    pub fn read_dir<P: AsRef<str>>(&'a self, path: P) -> SmbResult<SmbReadDir<'a>> {
+        trace!("opening directory {}", path.as_ref());
+        self.opendir(path).map(|fd| SmbReadDir::new(self, fd))
+    }
+
+    /// List the shares exposed by the server in the credentials.
+    ///
+    /// The share in the credentials is ignored, so it can be left empty
+    pub fn list_shares(&'a self) -> SmbResult<Vec<SmbShare>> {
+        trace!("listing shares on {}", self.server);
+        self.list_uri(Self::build_uri(&self.server, ""), SmbShare::from_dirent)
+    }
+
+    /// List the servers in `workgroup`, as reported by the master browsers
+    pub fn list_servers<S: AsRef<str>>(&'a self, workgroup: S) -> SmbResult<Vec<SmbServer>> {
+        trace!("listing servers in workgroup {}", workgroup.as_ref());
+        self.list_uri(
+            format!("{}{}", SMB_URI_SCHEME, workgroup.as_ref()),
+            SmbServer::from_dirent,
+        )
+    }
+
+    /// List the workgroups on the network.
+    ///
+    /// At most [`SmbOptions::browser_max_lmb_count`] master browsers are queried
+    pub fn list_workgroups(&'a self) -> SmbResult<Vec<SmbWorkgroup>> {
+        trace!("listing workgroups");
+        self.list_uri(SMB_URI_SCHEME.to_string(), SmbWorkgroup::from_dirent)
+    }
+
+    /// List the directory at the absolute `uri`, keeping the entries accepted by `f`
+    fn list_uri<T, F>(&'a self, uri: String, f: F) -> SmbResult<Vec<T>>
+    where
+        F: Fn(&SmbDirent) -> Option<T>,
+    {
+        let mut entries = Vec::new();
+        for dirent in SmbReadDir::new(self, self.opendir_uri(uri)?) {
+            let dirent = dirent?;
+            match f(&dirent) {
+                Some(entry) => entries.push(entry),
+                None => trace!("ignoring unexpected entry {:?}", dirent),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Watch the directory at `path` for changes.
+    ///
+    /// The returned iterator blocks until the server reports changes matching the filter in `options`;
+    /// it can be stopped, also from another thread, with the handle returned by [`SmbWatch::stopper`]
+    pub fn watch<P: AsRef<str>>(
+        &'a self,
+        path: P,
+        options: SmbWatchOptions,
+    ) -> SmbResult<SmbWatch<'a>> {
+        trace!("watching directory {} with {:?}", path.as_ref(), options);
+        self.opendir(path.as_ref())
+            .map(|fd| SmbWatch::new(self, fd, path, options))
+    }
+
+    /// Recursively walk the directory tree at `root`.
+    ///
+    /// The returned iterator can be configured to set depth limits, order, sorting and pruning
+    pub fn walk<P: AsRef<str>>(&'a self, root: P) -> SmbWalk<'a> {
+        trace!("walking directory tree at {}", root.as_ref());
+        SmbWalk::new(self, root)
+    }
+
+    /// Copy the file at `src` to `dst`, overwriting `dst` if it exists.
+    ///
+    /// The copy is performed server-side using SMB2 copy chunks, so data is not pulled through the client;
+    /// if the server doesn't support it, it falls back to a streamed copy.
+    /// `progress` is called with the amount of bytes copied so far and the total size of the file.
+    ///
+    /// Returns the amount of bytes copied
+    pub fn copy<P, F>(&'a self, src: P, dst: P, mut progress: F) -> SmbResult<u64>
+    where
+        P: AsRef<str>,
+        F: FnMut(u64, u64),
+    {
+        trace!("copying {} to {}", src.as_ref(), dst.as_ref());
+        let total = self.stat(src.as_ref())?.size;
+        self.copy_to(src.as_ref(), total, self, dst.as_ref(), &mut progress)
+    }
+
+    /// Copy the file at `src`, of `total` bytes, to `dst` on the share of `target`
+    fn copy_to(
+        &'a self,
+        src: &str,
+        total: u64,
+        target: &'a SmbClient,
+        dst: &str,
+        progress: &mut dyn FnMut(u64, u64),
+    ) -> SmbResult<u64> {
+        let mut reader = self.open_with(src, SmbOpenOptions::default().read(true))?;
+        let mut writer = target.open_with(
+            dst,
+            SmbOpenOptions::default()
+                .write(true)
+                .create(true)
+                .truncate(true),
+        )?;
+        match self.splice(&reader, &writer, total, progress) {
+            Err(e) if Self::is_unsupported(&e) => {
+                debug!(
+                    "server-side copy is not supported ({}); falling back to streamed copy",
+                    e
+                );
+                writer.set_len(0)?;
+                writer.seek(SeekFrom::Start(0))?;
+                reader.seek(SeekFrom::Start(0))?;
+                Self::stream_copy(&mut reader, &mut writer, total, progress)
+            }
+            res => res,
+        }
+    }
+
+    /// Upload the local file at `local` to `remote`, reporting the progress to `progress`.
+    ///
+    /// Unless `options` allow to overwrite it, the upload fails if `remote` exists.
+    /// When overwriting, the file is uploaded to a temporary sibling which replaces `remote` once complete;
+    /// a partial file is removed if the upload fails
+    pub fn upload<L, R, F>(
+        &'a self,
+        local: L,
+        remote: R,
+        options: SmbTransferOptions,
+        mut progress: F,
+    ) -> SmbResult<SmbTransferSummary>
+    where
+        L: AsRef<Path>,
+        R: AsRef<str>,
+        F: FnMut(SmbTransferProgress),
+    {
+        let (local, remote) = (local.as_ref(), remote.as_ref());
+        trace!(
+            "uploading {} to {} with {:?}",
+            local.display(),
+            remote,
+            options
+        );
+        options.validate()?;
+        let mut reader = File::open(local)?;
+        let metadata = reader.metadata()?;
+        let part = options
+            .overwrite
+            .then(|| format!("{}{}", remote, utils::partial_suffix()));
+        let dst = part.as_deref().unwrap_or(remote);
+        let mut writer = self.open_with(
+            dst,
+            SmbOpenOptions::default()
+                .write(true)
+                .create(true)
+                .exclusive(true),
+        )?;
+        let res = transfer(
+            &mut reader,
+            &mut writer,
+            metadata.len(),
+            options.buffer_size,
+            &mut progress,
+        );
+        drop(writer);
+        let res = res.and_then(|summary| match &part {
+            Some(part) => self.rename_replacing(part, remote).map(|_| summary),
+            None => Ok(summary),
+        });
+        if res.is_err() {
+            if let Err(e) = self.unlink(dst) {
+                warn!("failed to remove partial file {}: {}", dst, e);
+            }
+        }
+        let mut summary = res?;
+        if options.preserve_times {
+            let times = metadata
+                .accessed()
+                .and_then(|accessed| Ok((accessed, metadata.modified()?)));
+            match times
+                .map_err(SmbError::from)
+                .and_then(|(accessed, modified)| self.utimes(remote, accessed, modified))
+            {
+                Ok(()) => summary.times_preserved = true,
+                Err(e) => warn!("failed to preserve times of {}: {}", remote, e),
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Download the file at `remote` to the local file at `local`, reporting the progress to `progress`.
+    ///
+    /// Unless `options` allow to overwrite it, the download fails if `local` exists.
+    /// When overwriting, the file is downloaded to a temporary sibling which replaces `local` once complete;
+    /// a partial file is removed if the download fails
+    pub fn download<R, L, F>(
+        &'a self,
+        remote: R,
+        local: L,
+        options: SmbTransferOptions,
+        mut progress: F,
+    ) -> SmbResult<SmbTransferSummary>
+    where
+        R: AsRef<str>,
+        L: AsRef<Path>,
+        F: FnMut(SmbTransferProgress),
+    {
+        let (remote, local) = (remote.as_ref(), local.as_ref());
+        trace!(
+            "downloading {} to {} with {:?}",
+            remote,
+            local.display(),
+            options
+        );
+        options.validate()?;
+        let stat = self.stat(remote)?;
+        let mut reader = self.open_with(remote, SmbOpenOptions::default().read(true))?;
+        let part = options.overwrite.then(|| {
+            let mut part = local.as_os_str().to_owned();
+            part.push(utils::partial_suffix());
+            PathBuf::from(part)
+        });
+        let dst = part.as_deref().unwrap_or(local);
+        let mut writer = OpenOptions::new().write(true).create_new(true).open(dst)?;
+        let res = transfer(
+            &mut reader,
+            &mut writer,
+            stat.size,
+            options.buffer_size,
+            &mut progress,
+        );
+        let res = res.map(|mut summary| {
+            if options.preserve_times {
+                let times = FileTimes::new()
+                    .set_accessed(stat.accessed)
+                    .set_modified(stat.modified);
+                match writer.set_times(times) {
+                    Ok(()) => summary.times_preserved = true,
+                    Err(e) => warn!("failed to preserve times of {}: {}", local.display(), e),
+                }
+            }
+            summary
+        });
+        drop(writer);
+        let res = res.and_then(|summary| match &part {
+            Some(part) => std::fs::rename(part, local)
+                .map(|_| summary)
+                .map_err(SmbError::from),
+            None => Ok(summary),
+        });
+        if res.is_err() {
+            if let Err(e) = std::fs::remove_file(dst) {
+                warn!("failed to remove partial file {}: {}", dst.display(), e);
+            }
+        }
+        res
+    }
+
+    /// Move the entry at `src` to `dst`.
+    ///
+    /// The entry is renamed by the server if possible; otherwise, if enabled in `options`,
+    /// it is copied with its metadata and then removed. Returns the strategy which has been used.
+    ///
+    /// The copy fails with `EOPNOTSUPP` if the entry is or contains a reparse point (e.g. a junction),
+    /// and the part already copied is removed
+    pub fn move_path<P: AsRef<str>>(
+        &'a self,
+        src: P,
+        dst: P,
+        options: SmbMoveOptions,
+    ) -> SmbResult<SmbMoveStrategy> {
+        self.move_to(src, self, dst, options)
+    }
+
+    /// Move the entry at `src` to `dst` on the share of `target`, which may be on another server.
+    ///
+    /// See [`SmbClient::move_path`]
+    pub fn move_to<P: AsRef<str>>(
+        &'a self,
+        src: P,
+        target: &'a SmbClient,
+        dst: P,
+        options: SmbMoveOptions,
+    ) -> SmbResult<SmbMoveStrategy> {
+        let (src, dst) = (src.as_ref(), dst.as_ref());
+        trace!("moving {} to {}{} with {:?}", src, target.uri, dst, options);
+        let src_stat = self.stat(src)?;
+        if !options.overwrite {
+            return self.move_new(src, &src_stat, target, dst, options);
+        }
+        match self.rename_or_copy(src, &src_stat, target, dst, options) {
+            Err(e) if Self::is_already_existing(&e) => {
+                let dst_stat = target.stat(dst)?;
+                if dst_stat.mode.is_dir() != src_stat.mode.is_dir() {
+                    error!("cannot move {} to {}: types mismatch", src, dst);
+                    return Err(
+                        std::io::Error::from_raw_os_error(match dst_stat.mode.is_dir() {
+                            true => libc::EISDIR,
+                            false => libc::ENOTDIR,
+                        })
+                        .into(),
+                    );
+                }
+                if dst_stat.mode.is_dir() && !target.list_dir(dst)?.is_empty() {
+                    error!("cannot move {} to {}: destination is not empty", src, dst);
+                    return Err(std::io::Error::from_raw_os_error(libc::ENOTEMPTY).into());
+                }
+                target.replace_aside(dst, dst_stat.mode.is_dir(), || {
+                    self.rename_or_copy(src, &src_stat, target, dst, options)
+                })
+            }
+            res => res,
+        }
+    }
+
+    /// Move the entry at `src` to `dst` on the share of `target`, failing if `dst` exists
+    fn move_new(
+        &'a self,
+        src: &str,
+        src_stat: &SmbStat,
+        target: &'a SmbClient,
+        dst: &str,
+        options: SmbMoveOptions,
+    ) -> SmbResult<SmbMoveStrategy> {
+        // libsmbclient replaces an existing file on rename, so the destination is reserved
+        // with an exclusive create, which fails if it exists; the placeholder is then replaced
+        target.open_with(
+            dst,
+            SmbOpenOptions::default()
+                .write(true)
+                .create(true)
+                .exclusive(true),
+        )?;
+        match self.rename_uri_replacing(self.uri(src), target, dst) {
+            Ok(()) => Ok(SmbMoveStrategy::Rename),
+            Err(e) => {
+                if let Err(e) = target.unlink(dst) {
+                    warn!("failed to remove placeholder {}: {}", dst, e);
+                }
+                if options.copy_fallback && Self::is_unsupported(&e) {
+                    debug!(
+                        "cannot rename {} ({}); falling back to copy then delete",
+                        src, e
+                    );
+                    self.copy_then_delete(src, src_stat, target, dst)?;
+                    Ok(SmbMoveStrategy::CopyDelete)
+                } else {
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    /// Rename the file at `src` to `dst`, replacing it if it exists
+    fn rename_replacing(&self, src: &str, dst: &str) -> SmbResult<()> {
+        self.rename_uri_replacing(self.uri(src), self, dst)
+    }
+
+    /// Rename the entry at `orig_url` to the file at `dst` on the share of `target`, replacing it if it exists
+    fn rename_uri_replacing(
+        &self,
+        orig_url: String,
+        target: &SmbClient,
+        dst: &str,
+    ) -> SmbResult<()> {
+        // libsmbclient already replaces an existing file, but older versions fail with EEXIST
+        match self.rename_uri(orig_url.clone(), target.uri(dst)) {
+            Err(e) if Self::is_already_existing(&e) => {
+                target.unlink(dst)?;
+                self.rename_uri(orig_url, target.uri(dst))
+            }
+            res => res,
+        }
+    }
+
+    /// Rename `src` to `dst` on the share of `target`; if enabled in `options` and the server can't rename it,
+    /// copy then delete it
+    fn rename_or_copy(
+        &'a self,
+        src: &str,
+        src_stat: &SmbStat,
+        target: &'a SmbClient,
+        dst: &str,
+        options: SmbMoveOptions,
+    ) -> SmbResult<SmbMoveStrategy> {
+        match self.rename_uri(self.uri(src), target.uri(dst)) {
+            Ok(()) => Ok(SmbMoveStrategy::Rename),
+            Err(e) if options.copy_fallback && Self::is_unsupported(&e) => {
+                debug!(
+                    "cannot rename {} ({}); falling back to copy then delete",
+                    src, e
+                );
+                // the copy doesn't replace the destination
+                match target.stat(dst) {
+                    Ok(_) => Err(std::io::Error::from_raw_os_error(libc::EEXIST).into()),
+                    Err(e) if e.raw_os_error() == Some(libc::ENOENT) => self
+                        .copy_then_delete(src, src_stat, target, dst)
+                        .map(|_| SmbMoveStrategy::CopyDelete),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Move `dst` aside while `replace` moves another entry to it; then `dst` is removed,
+    /// or restored if `replace` fails
+    fn replace_aside<F>(&self, dst: &str, is_dir: bool, replace: F) -> SmbResult<SmbMoveStrategy>
+    where
+        F: FnOnce() -> SmbResult<SmbMoveStrategy>,
+    {
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let aside = format!("{}.pavao-replaced-{}", dst.trim_end_matches('/'), nanos);
+        self.rename_uri(self.uri(dst), self.uri(&aside))?;
+        match replace() {
+            Ok(strategy) => {
+                let res = match is_dir {
+                    true => self.rmdir(&aside),
+                    false => self.unlink(&aside),
+                };
+                if let Err(e) = res {
+                    warn!("failed to remove replaced entry {}: {}", aside, e);
+                }
+                Ok(strategy)
+            }
+            Err(e) => {
+                // make room to restore the replaced entry
+                self.remove_partial(dst);
+                if let Err(e) = self.rename_uri(self.uri(&aside), self.uri(dst)) {
+                    error!("failed to restore {} from {}: {}", dst, aside, e);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Copy the entry at `src` with its metadata to `dst` on the share of `target`, then remove `src`.
+    ///
+    /// If the copy fails, the part of the entry copied to `dst` is removed and `src` is left untouched
+    fn copy_then_delete(
+        &'a self,
+        src: &str,
+        src_stat: &SmbStat,
+        target: &'a SmbClient,
+        dst: &str,
+    ) -> SmbResult<()> {
+        if let Err(e) = self.copy_tree(src, src_stat, target, dst) {
+            target.remove_partial(dst);
+            return Err(e);
+        }
+        match src_stat.mode.is_dir() {
+            true => self.remove_dir_all(src),
+            false => self.unlink(src),
+        }
+    }
+
+    /// Copy the entry at `src` with its metadata to `dst` on the share of `target`.
+    ///
+    /// Reparse points (e.g. junctions and symlinks) can't be copied, so they fail with `EOPNOTSUPP`
+    fn copy_tree(
+        &'a self,
+        src: &str,
+        src_stat: &SmbStat,
+        target: &'a SmbClient,
+        dst: &str,
+    ) -> SmbResult<()> {
+        // stat doesn't carry the DOS attributes
+        let src_attributes = self
+            .get_attributes(src)
+            .unwrap_or(SmbFileAttributes::empty());
+        if src_attributes.contains(SmbFileAttributes::REPARSE_POINT) {
+            let error = std::io::Error::from_raw_os_error(libc::EOPNOTSUPP);
+            return Err(SmbError::with_path(error.into(), src));
+        }
+        if !src_stat.mode.is_dir() {
+            self.copy_to(src, src_stat.size, target, dst, &mut |_, _| {})?;
+            target.preserve_metadata(dst, src_stat.accessed, src_stat.modified, src_attributes);
+            return Ok(());
+        }
+        target.mkdir(dst, src_stat.mode)?;
+        // directories metadata is set once their content has been copied
+        let mut dirs = vec![(
+            dst.to_string(),
+            src_stat.accessed,
+            src_stat.modified,
+            src_attributes,
+        )];
+        for entry in self.walk(src) {
+            let entry = entry?;
+            if entry.is_reparse_point() {
+                let error = std::io::Error::from_raw_os_error(libc::EOPNOTSUPP);
+                return Err(SmbError::with_path(error.into(), &entry.path));
+            }
+            let dst_path = format!(
+                "{}{}",
+                dst.trim_end_matches('/'),
+                &entry.path[src.trim_end_matches('/').len()..]
+            );
+            // the listing carries the metadata except the permissions, which are read with stat
+            let info = &entry.info;
+            if entry.get_type() == SmbDirentType::Dir {
+                target.mkdir(&dst_path, self.stat(&entry.path)?.mode)?;
+                dirs.push((dst_path, info.atime, info.mtime, info.attributes()));
+            } else {
+                self.copy_to(&entry.path, info.size, target, &dst_path, &mut |_, _| {})?;
+                target.preserve_metadata(&dst_path, info.atime, info.mtime, info.attributes());
+            }
+        }
+        for (path, accessed, modified, attributes) in dirs.into_iter().rev() {
+            target.preserve_metadata(&path, accessed, modified, attributes);
+        }
+        Ok(())
+    }
+
+    /// Remove what a failed copy left at `path`, if anything
+    fn remove_partial(&self, path: &str) {
+        let res = match self.stat(path) {
+            Ok(stat) if stat.mode.is_dir() => self.remove_dir_all(path),
+            Ok(_) => self.unlink(path),
+            Err(_) => Ok(()),
+        };
+        if let Err(e) = res {
+            warn!("failed to remove partial entry {}: {}", path, e);
+        }
+    }
+
+    /// Copy `total` bytes from `src` to `dst` server-side
+    fn splice(
+        &self,
+        src: &SmbFile<'_>,
+        dst: &SmbFile<'_>,
+        total: u64,
+        progress: &mut dyn FnMut(u64, u64),
+    ) -> SmbResult<u64> {
+        let splice_fn = self.get_fn(self.ctx()?, smbc_getFunctionSplice)?;
+        let count = off_t::try_from(total).map_err(|_| SmbError::BadValue)?;
+        let mut state = SpliceState { total, progress };
+        let copied = splice_fn(
+            self.ctx()?,
+            src.fd(),
+            dst.fd(),
+            count,
+            Some(Self::splice_wrapper),
+            &mut state as *mut SpliceState as *mut c_void,
+        );
+        utils::to_result_with_ioerror(copied as u64, copied)
+    }
+
+    /// Copy `total` bytes from `reader` to `writer` through the client
+    fn stream_copy(
+        reader: &mut SmbFile<'_>,
+        writer: &mut SmbFile<'_>,
+        total: u64,
+        progress: &mut dyn FnMut(u64, u64),
+    ) -> SmbResult<u64> {
+        let mut buf = vec![0u8; COPY_BUFFER_SIZE];
+        let mut copied = 0;
+        loop {
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            writer.write_all(&buf[..n])?;
+            copied += n as u64;
+            progress(copied, total);
+        }
+        Ok(copied)
+    }
+
+    /// Open a new print job on the print queue `print_queue`.
+    ///
+    /// Data written to the returned handle is sent to the printer; the job is submitted by
+    /// [`SmbPrintJob::finish`] or on drop
+    pub fn open_print_job<S: AsRef<str>>(&'a self, print_queue: S) -> SmbResult<SmbPrintJob<'a>> {
+        trace!("opening print job on {} queue", print_queue.as_ref());
+        let open_print_job_fn = self.get_fn(self.ctx()?, smbc_getFunctionOpenPrintJob)?;
+        let print_queue = utils::str_to_cstring(self.uri(print_queue))?;
+        let fd = utils::result_from_ptr_mut(open_print_job_fn(self.ctx()?, print_queue.as_ptr()))?;
+        trace!("opened print job with file descriptor {:?}", fd);
+        Ok(SmbPrintJob::new(SmbFile::new(self, fd)))
+    }
 }
 
 // -- destructor
@@ -511,7 +2153,7 @@ impl Drop for SmbClient {
                 trace!("closing smbclient");
                 smbc_free_context(context.get(), 1_i32);
                 // set context to null
-                context.set(ptr::null_mut());
+                *context = SmbContext::null();
             }
         }
         trace!("smbclient context freed");
@@ -520,7 +2162,7 @@ impl Drop for SmbClient {
 
 #[cfg(test)]
 mod test {
-    use std::io::{Cursor, Read};
+    use std::io::Cursor;
     use std::time::UNIX_EPOCH;
 
     use pretty_assertions::{assert_eq, assert_ne};
@@ -528,7 +2170,10 @@ mod test {
 
     use super::*;
     use crate::test::TestCtx;
-    use crate::{mock, SmbDirentType};
+    use crate::{
+        mock, SmbAccessMask, SmbAceFlags, SmbAceType, SmbDirentType, SmbShareType, SmbTrustee,
+        SmbWalkEntry, SmbWalkOrder, SmbWatchEvent,
+    };
 
     #[test]
     #[serial]
@@ -558,6 +2203,96 @@ mod test {
         finalize_ctx(ctx);
     }
 
+    #[test]
+    #[serial]
+    fn should_set_debug_level() {
+        mock::logger();
+        let ctx = init_ctx();
+        assert_eq!(ctx.client.get_debug_level().unwrap(), 0);
+        ctx.client.set_debug_level(3).unwrap();
+        assert_eq!(ctx.client.get_debug_level().unwrap(), 3);
+        assert!(ctx.client.list_dir("/cargo-test").is_ok());
+        ctx.client.set_debug_level(0).unwrap();
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_reject_unknown_parameters() {
+        mock::logger();
+        let credentials = SmbCredentials::default()
+            .server("smb://localhost")
+            .share("/temp");
+        assert!(matches!(
+            SmbClient::new(
+                credentials.clone(),
+                SmbOptions::default().parameter("pavao bogus parameter", "yes")
+            ),
+            Err(SmbError::BadOption(_))
+        ));
+        // unknown parameters in the configuration file are rejected too
+        let mut conf = tempfile::NamedTempFile::new().unwrap();
+        conf.write_all(b"[global]\n\tpavao bogus parameter = yes\n")
+            .unwrap();
+        assert!(matches!(
+            SmbClient::new(credentials, SmbOptions::default().config_file(conf.path())),
+            Err(SmbError::BadOption(_))
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn should_get_required_session_security() {
+        mock::logger();
+        let ctx = init_ctx();
+        assert_eq!(
+            ctx.client.required_session_security().unwrap(),
+            SmbSessionSecurity {
+                signed: false,
+                encrypted: false
+            }
+        );
+        finalize_ctx(ctx);
+        let ctx = TestCtx::with_options(SmbOptions::default().signing(SmbSigningPolicy::Required));
+        assert_eq!(
+            ctx.client.required_session_security().unwrap(),
+            SmbSessionSecurity {
+                signed: true,
+                encrypted: false
+            }
+        );
+        finalize_ctx(ctx);
+        let ctx = TestCtx::with_options(
+            SmbOptions::default().encryption_level(SmbEncryptionLevel::Require),
+        );
+        assert_eq!(
+            ctx.client.required_session_security().unwrap(),
+            SmbSessionSecurity {
+                signed: true,
+                encrypted: true
+            }
+        );
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_request_posix_extensions() {
+        mock::logger();
+        let ctx = init_ctx();
+        assert_eq!(ctx.client.options().unwrap().posix_extensions, false);
+        finalize_ctx(ctx);
+        let ctx = TestCtx::with_options(
+            SmbOptions::default()
+                .one_share_per_server(true)
+                .posix_extensions(true),
+        );
+        assert_eq!(ctx.client.options().unwrap().posix_extensions, true);
+        // POSIX extensions imply case-sensitive names
+        assert_eq!(ctx.client.options().unwrap().case_sensitive, true);
+        finalize_ctx(ctx);
+    }
+
     #[test]
     #[serial]
     fn should_get_workgroup() {
@@ -657,9 +2392,9 @@ mod test {
             .is_ok());
         // list dir
         let mut entries = ctx.client.list_dir("/cargo-test").unwrap();
-        entries.sort_by(|a, b| a.name().cmp(&b.name()));
+        entries.sort_by(|a, b| a.name().cmp(b.name()));
         assert_eq!(entries.len(), 3);
-        let abc = entries.get(0).unwrap();
+        let abc = entries.first().unwrap();
         assert_eq!(abc.name(), "abc");
         assert_eq!(abc.get_type(), SmbDirentType::File);
         let def = entries.get(1).unwrap();
@@ -684,9 +2419,9 @@ mod test {
             .is_ok());
         // list dir
         let mut entries = ctx.client.list_dir("/cargo-test").unwrap();
-        entries.sort_by(|a, b| a.name().cmp(&b.name()));
+        entries.sort_by(|a, b| a.name().cmp(b.name()));
         assert_eq!(entries.len(), 3);
-        let abc = entries.get(0).unwrap();
+        let abc = entries.first().unwrap();
         assert_eq!(abc.name(), "ghi");
         assert_eq!(abc.get_type(), SmbDirentType::File);
         let def = entries.get(1).unwrap();
@@ -735,39 +2470,183 @@ mod test {
 
     #[test]
     #[serial]
-    fn should_stat() {
+    fn should_get_effective_options() {
         mock::logger();
         let ctx = init_ctx();
-        // Create file
-        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
-        // stat
-        let file = ctx.client.stat("/cargo-test/test").unwrap();
-        assert_ne!(file.accessed, UNIX_EPOCH);
-        assert_ne!(file.blksize, 0);
-        assert_ne!(file.blocks, 0);
-        //assert_eq!(file.mode, SmbMode::from(0o744));
-        assert_eq!(file.size, 14);
+        let options = ctx.client.options().unwrap();
+        assert_eq!(options.case_sensitive, true);
+        assert_eq!(options.one_share_per_server, true);
+        assert_eq!(options.encryption_level, SmbEncryptionLevel::None);
+        assert_eq!(options.open_share_mode, SmbShareMode::DenyNone);
+        assert_eq!(options.user, "test");
+        assert_eq!(options.workgroup, "pavao");
         finalize_ctx(ctx);
     }
 
     #[test]
     #[serial]
-    fn should_chmod() {
+    fn should_purge_cached_connections() {
         mock::logger();
         let ctx = init_ctx();
-        // Create file
-        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
-        let _ = ctx.client.chmod("/cargo-test/test", SmbMode::from(0o755)); // NOTE: may not be supported by the server
+        assert!(ctx.client.list_dir("/cargo-test").is_ok());
+        let stats = ctx.client.cache_stats().unwrap();
+        assert!(stats.cached >= 1);
+        assert!(stats.added >= 1);
+        assert!(stats.hits >= 1);
+        assert_eq!(ctx.client.purge_cached_connections().unwrap(), stats.cached);
+        assert_eq!(ctx.client.cache_stats().unwrap().cached, 0);
+        // connections are opened again when needed
+        assert!(ctx.client.list_dir("/cargo-test").is_ok());
+        assert!(ctx.client.cache_stats().unwrap().cached >= 1);
         finalize_ctx(ctx);
     }
 
     #[test]
     #[serial]
-    fn should_build_uri() {
+    fn should_upload_and_download() {
         mock::logger();
         let ctx = init_ctx();
-
-        assert!(ctx.client.uri("/test").as_str().ends_with("/temp/test"));
+        let dir = std::env::temp_dir().join(format!("pavao-transfer-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let local = dir.join("upload.bin");
+        let data = vec![0xab; 200_000];
+        std::fs::write(&local, &data).unwrap();
+        let mut transferred = 0;
+        let summary = ctx
+            .client
+            .upload(
+                &local,
+                "/cargo-test/transfer.bin",
+                SmbTransferOptions::default().buffer_size(65536),
+                |progress| {
+                    assert_eq!(progress.total, 200_000);
+                    assert!(progress.transferred > transferred);
+                    transferred = progress.transferred;
+                },
+            )
+            .unwrap();
+        assert_eq!(summary.bytes, 200_000);
+        assert_eq!(summary.times_preserved, true);
+        assert_eq!(transferred, 200_000);
+        assert_eq!(
+            ctx.client.stat("/cargo-test/transfer.bin").unwrap().size,
+            200_000
+        );
+        // the destination exists
+        assert!(ctx
+            .client
+            .upload(
+                &local,
+                "/cargo-test/transfer.bin",
+                SmbTransferOptions::default(),
+                |_| {}
+            )
+            .is_err());
+        // download
+        let downloaded = dir.join("download.bin");
+        let summary = ctx
+            .client
+            .download(
+                "/cargo-test/transfer.bin",
+                &downloaded,
+                SmbTransferOptions::default(),
+                |_| {},
+            )
+            .unwrap();
+        assert_eq!(summary.bytes, 200_000);
+        assert_eq!(std::fs::read(&downloaded).unwrap(), data);
+        assert_eq!(
+            std::fs::metadata(&downloaded)
+                .unwrap()
+                .modified()
+                .unwrap()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            ctx.client
+                .stat("/cargo-test/transfer.bin")
+                .unwrap()
+                .modified
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        );
+        assert!(ctx
+            .client
+            .download(
+                "/cargo-test/transfer.bin",
+                &downloaded,
+                SmbTransferOptions::default(),
+                |_| {}
+            )
+            .is_err());
+        assert!(ctx
+            .client
+            .download(
+                "/cargo-test/transfer.bin",
+                &downloaded,
+                SmbTransferOptions::default().overwrite(true),
+                |_| {}
+            )
+            .is_ok());
+        assert_eq!(std::fs::read(&downloaded).unwrap(), data);
+        // overwrite replaces the remote file once uploaded, leaving no partial file
+        std::fs::write(&local, b"Hello\n").unwrap();
+        assert!(ctx
+            .client
+            .upload(
+                &local,
+                "/cargo-test/transfer.bin",
+                SmbTransferOptions::default().overwrite(true),
+                |_| {}
+            )
+            .is_ok());
+        assert_eq!(
+            read_file_at(&ctx.client, "/cargo-test/transfer.bin"),
+            b"Hello\n"
+        );
+        assert_eq!(ctx.client.list_dir("/cargo-test").unwrap().len(), 1);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_stat() {
+        mock::logger();
+        let ctx = init_ctx();
+        // Create file
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
+        // stat
+        let file = ctx.client.stat("/cargo-test/test").unwrap();
+        assert_ne!(file.accessed, UNIX_EPOCH);
+        assert_ne!(file.blksize, 0);
+        assert_ne!(file.blocks, 0);
+        //assert_eq!(file.mode, SmbMode::from(0o744));
+        assert_eq!(file.size, 14);
+        assert!(file.created <= file.modified);
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_chmod() {
+        mock::logger();
+        let ctx = init_ctx();
+        // Create file
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
+        let _ = ctx.client.chmod("/cargo-test/test", SmbMode::from(0o755)); // NOTE: may not be supported by the server
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_build_uri() {
+        mock::logger();
+        let ctx = init_ctx();
+
+        assert!(ctx.client.uri("/test").as_str().ends_with("/temp/test"));
         finalize_ctx(ctx);
     }
 
@@ -837,6 +2716,895 @@ mod test {
         finalize_ctx(ctx);
     }
 
+    #[test]
+    #[serial]
+    fn should_shrink_file_with_set_len() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
+        let file = ctx
+            .client
+            .open_with(
+                "/cargo-test/test",
+                SmbOpenOptions::default().read(true).write(true),
+            )
+            .unwrap();
+        assert!(file.set_len(5).is_ok());
+        drop(file);
+        assert_eq!(ctx.client.stat("/cargo-test/test").unwrap().size, 5);
+        assert_eq!(read_file_at(&ctx.client, "/cargo-test/test"), b"Hello");
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_extend_file_with_zeros_with_set_len() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello");
+        let file = ctx
+            .client
+            .open_with(
+                "/cargo-test/test",
+                SmbOpenOptions::default().read(true).write(true),
+            )
+            .unwrap();
+        assert!(file.set_len(10).is_ok());
+        drop(file);
+        assert_eq!(ctx.client.stat("/cargo-test/test").unwrap().size, 10);
+        assert_eq!(
+            read_file_at(&ctx.client, "/cargo-test/test"),
+            b"Hello\0\0\0\0\0"
+        );
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_not_move_cursor_with_set_len() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
+        let mut file = ctx
+            .client
+            .open_with(
+                "/cargo-test/test",
+                SmbOpenOptions::default().read(true).write(true),
+            )
+            .unwrap();
+        assert_eq!(file.seek(SeekFrom::Start(7)).unwrap(), 7);
+        // shrink before the cursor; cursor must be preserved
+        assert!(file.set_len(3).is_ok());
+        assert_eq!(file.stream_position().unwrap(), 7);
+        // writing past the end must fill the gap with zeros
+        assert!(file.write_all(b"!").is_ok());
+        drop(file);
+        assert_eq!(
+            read_file_at(&ctx.client, "/cargo-test/test"),
+            b"Hel\0\0\0\0!"
+        );
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_get_xattr() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
+        let mode = ctx
+            .client
+            .get_xattr("/cargo-test/test", "system.dos_attr.mode")
+            .unwrap();
+        assert!(mode.starts_with("0x"));
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_set_xattr() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
+        assert!(ctx
+            .client
+            .set_xattr(
+                "/cargo-test/test",
+                "system.dos_attr.mode",
+                "0x20",
+                SmbXattrFlag::Any
+            )
+            .is_ok());
+        let mode = ctx
+            .client
+            .get_xattr("/cargo-test/test", "system.dos_attr.mode")
+            .unwrap();
+        assert_eq!(
+            u16::from_str_radix(mode.trim_start_matches("0x"), 16).unwrap() & 0x20,
+            0x20
+        );
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_list_xattr() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
+        let names = ctx.client.list_xattr("/cargo-test/test").unwrap();
+        assert!(names.iter().any(|x| x == "system.dos_attr.mode"));
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_get_xattr_value() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
+        assert!(ctx
+            .client
+            .set_attributes("/cargo-test/test", SmbFileAttributes::HIDDEN)
+            .is_ok());
+        let value = ctx
+            .client
+            .get_xattr("/cargo-test/test", XATTR_DOS_ATTR_MODE)
+            .unwrap();
+        assert!(!value.is_empty());
+        assert!(SmbFileAttributes::from_xattr(&value)
+            .unwrap()
+            .contains(SmbFileAttributes::HIDDEN));
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_fail_getting_unknown_xattr() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
+        assert!(ctx
+            .client
+            .get_xattr("/cargo-test/test", "system.foobar")
+            .is_err());
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_get_security_descriptor() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
+        let sd = ctx
+            .client
+            .get_security_descriptor("/cargo-test/test", false)
+            .unwrap();
+        assert!(matches!(sd.owner, Some(SmbTrustee::Sid(_))));
+        assert!(matches!(sd.group, Some(SmbTrustee::Sid(_))));
+        assert!(!sd.acl.is_empty());
+        assert!(ctx
+            .client
+            .get_security_descriptor("/cargo-test/test", true)
+            .is_ok());
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_add_and_remove_ace() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
+        let ace = SmbAce::new(
+            SmbTrustee::Sid("S-1-1-0".to_string()),
+            SmbAceType::Allowed,
+            SmbAceFlags::empty(),
+            SmbAccessMask::READ,
+        );
+        // NOTE: may not be supported by the server
+        if ctx.client.add_ace("/cargo-test/test", ace.clone()).is_ok() {
+            let sd = ctx
+                .client
+                .get_security_descriptor("/cargo-test/test", false)
+                .unwrap();
+            assert!(sd.acl.contains(&ace));
+            assert_eq!(
+                ctx.client.remove_ace("/cargo-test/test", &ace).unwrap(),
+                true
+            );
+        }
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_get_and_set_attributes() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/test", "Hello, World!\n");
+        assert!(ctx
+            .client
+            .set_attributes(
+                "/cargo-test/test",
+                SmbFileAttributes::READONLY | SmbFileAttributes::HIDDEN
+            )
+            .is_ok());
+        let attributes = ctx.client.get_attributes("/cargo-test/test").unwrap();
+        assert!(attributes.contains(SmbFileAttributes::READONLY));
+        assert!(attributes.contains(SmbFileAttributes::HIDDEN));
+        let entry = ctx
+            .client
+            .list_dirplus2("/cargo-test")
+            .unwrap()
+            .into_iter()
+            .find(|entry| entry.info.name() == "test")
+            .unwrap();
+        assert!(entry
+            .stat
+            .attributes
+            .unwrap()
+            .contains(SmbFileAttributes::READONLY));
+        // an empty set clears the attributes
+        assert!(ctx
+            .client
+            .set_attributes("/cargo-test/test", SmbFileAttributes::empty())
+            .is_ok());
+        let attributes = ctx.client.get_attributes("/cargo-test/test").unwrap();
+        assert!(!attributes.intersects(SmbFileAttributes::READONLY | SmbFileAttributes::HIDDEN));
+        assert!(ctx.client.stat("/cargo-test").unwrap().attributes.is_none());
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_print_job() {
+        mock::logger();
+        let ctx = TestCtx::with_print_queue("/printer");
+        let mut job = ctx.client.open_print_job("/job").unwrap();
+        job.write_all(b"Hello, World!\n").unwrap();
+        job.finish().unwrap();
+        // the print command of the share moves the spooled job aside
+        ctx.exec(
+            "for i in 1 2 3 4 5; do grep -qs 'Hello, World!' /mnt/printer/*.printed && exit 0; sleep 1; done; exit 1",
+        );
+        // jobs are listed and cancelled with RAP, which is only available over SMB1
+        assert!(ctx.client.list_print_jobs("/").is_err());
+        assert!(ctx.client.cancel_print_job("/", 1).is_err());
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_read_dir() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/abc", "Hello, World!\n");
+        create_file_at(&ctx.client, "/cargo-test/def", "Hello, World!\n");
+        assert!(ctx
+            .client
+            .mkdir("/cargo-test/jfk", SmbMode::from(0o755))
+            .is_ok());
+        let mut names: Vec<String> = ctx
+            .client
+            .read_dir("/cargo-test")
+            .unwrap()
+            .map(|x| x.unwrap().name().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["abc", "def", "jfk"]);
+        // early exit
+        assert_eq!(
+            ctx.client.read_dir("/cargo-test").unwrap().take(1).count(),
+            1
+        );
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_resume_read_dir() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/abc", "Hello, World!\n");
+        create_file_at(&ctx.client, "/cargo-test/def", "Hello, World!\n");
+        create_file_at(&ctx.client, "/cargo-test/ghi", "Hello, World!\n");
+        let mut dir = ctx.client.read_dir("/cargo-test").unwrap();
+        let first = dir.next().unwrap().unwrap();
+        let pos = dir.tell().unwrap();
+        let rest: Vec<String> = dir
+            .by_ref()
+            .map(|x| x.unwrap().name().to_string())
+            .collect();
+        assert_eq!(rest.len(), 2);
+        assert!(!rest.contains(&first.name().to_string()));
+        // resume from saved position
+        assert!(dir.seek(pos).is_ok());
+        let resumed: Vec<String> = dir.map(|x| x.unwrap().name().to_string()).collect();
+        assert_eq!(resumed, rest);
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_fail_read_dir_on_missing_directory() {
+        mock::logger();
+        let ctx = init_ctx();
+        assert!(ctx.client.read_dir("/cargo-test/missing").is_err());
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_list_dirplus2() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/abc", "Hello, World!\n");
+        assert!(ctx
+            .client
+            .mkdir("/cargo-test/jfk", SmbMode::from(0o755))
+            .is_ok());
+        let mut entries = ctx.client.list_dirplus2("/cargo-test").unwrap();
+        entries.sort_by(|a, b| a.name().cmp(b.name()));
+        assert_eq!(entries.len(), 2);
+        let abc = entries.first().unwrap();
+        assert_eq!(abc.name(), "abc");
+        assert_eq!(abc.get_type(), SmbDirentType::File);
+        assert_eq!(abc.stat.size, 14);
+        assert!(abc.stat.mode.is_file());
+        let jfk = entries.get(1).unwrap();
+        assert_eq!(jfk.name(), "jfk");
+        assert_eq!(jfk.get_type(), SmbDirentType::Dir);
+        assert!(jfk.stat.mode.is_dir());
+        assert!(jfk
+            .stat
+            .attributes
+            .unwrap()
+            .contains(SmbFileAttributes::DIRECTORY));
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_create_dir_all() {
+        mock::logger();
+        let ctx = init_ctx();
+        assert!(ctx
+            .client
+            .create_dir_all("/cargo-test/a/b/c", SmbMode::from(0o755))
+            .is_ok());
+        assert!(ctx.client.stat("/cargo-test/a/b/c").unwrap().mode.is_dir());
+        // existing directories are not an error
+        assert!(ctx
+            .client
+            .create_dir_all("/cargo-test/a/b/", SmbMode::from(0o755))
+            .is_ok());
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_not_create_dir_all_over_file() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/a", "Hello, World!\n");
+        assert!(ctx
+            .client
+            .create_dir_all("/cargo-test/a", SmbMode::from(0o755))
+            .is_err());
+        assert!(ctx
+            .client
+            .create_dir_all("/cargo-test/a/b", SmbMode::from(0o755))
+            .is_err());
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_remove_dir_all() {
+        mock::logger();
+        let ctx = init_ctx();
+        assert!(ctx
+            .client
+            .create_dir_all("/cargo-test/a/b", SmbMode::from(0o755))
+            .is_ok());
+        create_file_at(&ctx.client, "/cargo-test/a/abc", "Hello, World!\n");
+        create_file_at(&ctx.client, "/cargo-test/a/b/def", "Hello, World!\n");
+        ctx.client
+            .set_attributes("/cargo-test/a/b/def", SmbFileAttributes::READONLY)
+            .unwrap();
+        assert!(ctx
+            .client
+            .get_attributes("/cargo-test/a/b/def")
+            .unwrap()
+            .contains(SmbFileAttributes::READONLY));
+        // the read-only file is cleared, then removed
+        ctx.client.remove_dir_all("/cargo-test/a").unwrap();
+        assert!(ctx.client.stat("/cargo-test/a").is_err());
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_report_path_on_remove_dir_all_failure() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/abc", "Hello, World!\n");
+        assert!(matches!(
+            ctx.client.remove_dir_all("/cargo-test/abc"),
+            Err(SmbError::Path { path, .. }) if path == "/cargo-test/abc"
+        ));
+        assert!(matches!(
+            ctx.client.remove_dir_all_with(
+                "/cargo-test/missing",
+                SmbRemoveDirOptions::default().continue_on_error(true)
+            ),
+            Err(SmbError::Path { .. })
+        ));
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_walk_depth_first() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_walk_tree(&ctx.client);
+        let paths: Vec<String> = ctx
+            .client
+            .walk("/cargo-test")
+            .sort_by(|a, b| a.name().cmp(b.name()))
+            .map(|x| x.unwrap().path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/cargo-test/a",
+                "/cargo-test/a/b",
+                "/cargo-test/a/b/c.txt",
+                "/cargo-test/a/d.txt",
+                "/cargo-test/e.txt",
+            ]
+        );
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_walk_breadth_first() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_walk_tree(&ctx.client);
+        let entries: Vec<(String, usize)> = ctx
+            .client
+            .walk("/cargo-test")
+            .order(SmbWalkOrder::BreadthFirst)
+            .sort_by(|a, b| a.name().cmp(b.name()))
+            .map(|x| x.unwrap())
+            .map(|x| (x.path, x.depth))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("/cargo-test/a".to_string(), 1),
+                ("/cargo-test/e.txt".to_string(), 1),
+                ("/cargo-test/a/b".to_string(), 2),
+                ("/cargo-test/a/d.txt".to_string(), 2),
+                ("/cargo-test/a/b/c.txt".to_string(), 3),
+            ]
+        );
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_walk_with_depth_limits_and_pruning() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_walk_tree(&ctx.client);
+        let mut paths: Vec<String> = ctx
+            .client
+            .walk("/cargo-test")
+            .min_depth(2)
+            .max_depth(2)
+            .map(|x| x.unwrap().path)
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["/cargo-test/a/b", "/cargo-test/a/d.txt"]);
+        let mut paths: Vec<String> = ctx
+            .client
+            .walk("/cargo-test")
+            .filter_entry(|x| x.name() != "b")
+            .map(|x| x.unwrap().path)
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec!["/cargo-test/a", "/cargo-test/a/d.txt", "/cargo-test/e.txt"]
+        );
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_report_errors_while_walking() {
+        mock::logger();
+        let ctx = init_ctx();
+        let entries: Vec<SmbResult<SmbWalkEntry>> =
+            ctx.client.walk("/cargo-test/missing").collect();
+        assert_eq!(entries.len(), 1);
+        assert!(matches!(
+            entries.first().unwrap(),
+            Err(SmbError::Path { path, .. }) if path == "/cargo-test/missing"
+        ));
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_copy_file() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/src", "Hello, World!\n");
+        let mut last_progress = (0, 0);
+        assert_eq!(
+            ctx.client
+                .copy("/cargo-test/src", "/cargo-test/dst", |done, total| {
+                    last_progress = (done, total)
+                })
+                .unwrap(),
+            14
+        );
+        assert_eq!(last_progress, (14, 14));
+        assert_eq!(
+            read_file_at(&ctx.client, "/cargo-test/dst"),
+            b"Hello, World!\n"
+        );
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_overwrite_on_copy() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/src", "Hello\n");
+        create_file_at(&ctx.client, "/cargo-test/dst", "Hello, World!\n");
+        assert_eq!(
+            ctx.client
+                .copy("/cargo-test/src", "/cargo-test/dst", |_, _| {})
+                .unwrap(),
+            6
+        );
+        assert_eq!(read_file_at(&ctx.client, "/cargo-test/dst"), b"Hello\n");
+        assert!(ctx
+            .client
+            .copy("/cargo-test/missing", "/cargo-test/dst", |_, _| {})
+            .is_err());
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_watch_changes() {
+        mock::logger();
+        let ctx = init_ctx();
+        // the changes are made on the server, since the client is busy watching
+        assert!(watch_for(
+            &ctx,
+            "printf 'Hello\\n' > /mnt/tmp/cargo-test/file.txt",
+            SmbWatchEvent::Added("/cargo-test/file.txt".to_string())
+        ));
+        assert!(watch_for(
+            &ctx,
+            "printf 'World\\n' >> /mnt/tmp/cargo-test/file.txt",
+            SmbWatchEvent::Modified("/cargo-test/file.txt".to_string())
+        ));
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_stop_watch() {
+        mock::logger();
+        let ctx = init_ctx();
+        let mut watch = ctx
+            .client
+            .watch(
+                "/cargo-test",
+                SmbWatchOptions::default()
+                    .recursive(true)
+                    .poll_interval(Duration::from_millis(100)),
+            )
+            .unwrap();
+        watch.stopper().stop();
+        assert!(watch.next().is_none());
+        drop(watch);
+        assert!(ctx
+            .client
+            .watch("/cargo-test/missing", SmbWatchOptions::default())
+            .is_err());
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_list_shares() {
+        mock::logger();
+        let ctx = init_ctx();
+        let shares = ctx.client.list_shares().unwrap();
+        let temp = shares
+            .iter()
+            .find(|share| share.name == "temp")
+            .expect("temp share not found");
+        assert_eq!(temp.share_type, SmbShareType::Disk);
+        assert!(!temp.is_hidden());
+        assert!(shares
+            .iter()
+            .any(|share| share.share_type == SmbShareType::Ipc && share.is_hidden()));
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_read_and_write_streams() {
+        mock::logger();
+        // streams are stored in xattrs by the share
+        let ctx = TestCtx::with_share("/streams");
+        create_file_at(&ctx.client, "/cargo-test/file.txt", "Hello, World!\n");
+        let mut stream = ctx
+            .client
+            .open_stream_with(
+                "/cargo-test/file.txt",
+                "Zone.Identifier",
+                SmbOpenOptions::default().create(true).write(true),
+            )
+            .unwrap();
+        stream.write_all(b"[ZoneTransfer]\nZoneId=3\n").unwrap();
+        drop(stream);
+        assert_eq!(
+            ctx.client
+                .stream_info("/cargo-test/file.txt", "Zone.Identifier")
+                .unwrap(),
+            SmbStreamInfo {
+                name: "Zone.Identifier".to_string(),
+                size: 24,
+            }
+        );
+        assert_eq!(
+            read_file_at(&ctx.client, "/cargo-test/file.txt:Zone.Identifier"),
+            b"[ZoneTransfer]\nZoneId=3\n"
+        );
+        // default stream is untouched
+        assert_eq!(ctx.client.stat("/cargo-test/file.txt").unwrap().size, 14);
+        ctx.client
+            .remove_stream("/cargo-test/file.txt", "Zone.Identifier")
+            .unwrap();
+        assert!(ctx
+            .client
+            .stream_info("/cargo-test/file.txt", "Zone.Identifier")
+            .is_err());
+        assert!(ctx
+            .client
+            .open_stream_with("/cargo-test/file.txt", "a:b", SmbOpenOptions::default())
+            .is_err());
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_access_snapshots() {
+        mock::logger();
+        // the share is configured with shadow_copy2
+        let ctx = TestCtx::with_share("/snapshots");
+        let snapshot = SmbSnapshot::from_str("@GMT-2024.01.02-03.04.05").unwrap();
+        // fake the snapshots on the server, as a snapshot tool would do
+        ctx.exec(
+            "mkdir -p /mnt/snapshots/.snapshots/@GMT-2024.01.02-03.04.05/cargo-test \
+             /mnt/snapshots/.snapshots/@GMT-2024.02.03-04.05.06/cargo-test \
+             /mnt/snapshots/.snapshots/not-a-snapshot \
+             && touch /mnt/snapshots/.snapshots/@GMT-2024.03.04-05.06.07 \
+             && printf 'old\\n' > /mnt/snapshots/.snapshots/@GMT-2024.01.02-03.04.05/cargo-test/file.txt",
+        );
+        create_file_at(&ctx.client, "/cargo-test/file.txt", "Hello, World!\n");
+        let other = SmbSnapshot::from_str("@GMT-2024.02.03-04.05.06").unwrap();
+        assert_eq!(
+            ctx.client.list_snapshots("/cargo-test/file.txt").unwrap(),
+            vec![snapshot, other]
+        );
+        // the file doesn't exist in the second snapshot
+        assert!(ctx.client.stat_at("/cargo-test/file.txt", other).is_err());
+        assert_eq!(
+            ctx.client
+                .stat_at("/cargo-test/file.txt", snapshot)
+                .unwrap()
+                .size,
+            4
+        );
+        assert_eq!(
+            ctx.client
+                .list_dir_at("/cargo-test", snapshot)
+                .unwrap()
+                .iter()
+                .map(|dirent| dirent.name().to_string())
+                .collect::<Vec<String>>(),
+            vec!["file.txt".to_string()]
+        );
+        let mut file = ctx
+            .client
+            .open_with_at(
+                "/cargo-test/file.txt",
+                snapshot,
+                SmbOpenOptions::default().read(true),
+            )
+            .unwrap();
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).unwrap();
+        assert_eq!(content, b"old\n");
+        drop(file);
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_move_path() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_file_at(&ctx.client, "/cargo-test/a.txt", "Hello\n");
+        create_file_at(&ctx.client, "/cargo-test/b.txt", "World\n");
+        // no replace by default
+        assert!(ctx
+            .client
+            .move_path(
+                "/cargo-test/a.txt",
+                "/cargo-test/b.txt",
+                SmbMoveOptions::default()
+            )
+            .is_err());
+        assert_eq!(read_file_at(&ctx.client, "/cargo-test/b.txt"), b"World\n");
+        assert_eq!(
+            ctx.client
+                .move_path(
+                    "/cargo-test/a.txt",
+                    "/cargo-test/b.txt",
+                    SmbMoveOptions::default().overwrite(true)
+                )
+                .unwrap(),
+            SmbMoveStrategy::Rename
+        );
+        assert_eq!(read_file_at(&ctx.client, "/cargo-test/b.txt"), b"Hello\n");
+        assert!(ctx.client.stat("/cargo-test/a.txt").is_err());
+        // a file can't replace a directory
+        ctx.client
+            .mkdir("/cargo-test/dir", SmbMode::from(0o755))
+            .unwrap();
+        assert!(ctx
+            .client
+            .move_path(
+                "/cargo-test/b.txt",
+                "/cargo-test/dir",
+                SmbMoveOptions::default().overwrite(true)
+            )
+            .is_err());
+        assert_eq!(read_file_at(&ctx.client, "/cargo-test/b.txt"), b"Hello\n");
+        // a directory replaces an empty directory, which is moved aside then removed
+        ctx.client
+            .mkdir("/cargo-test/src", SmbMode::from(0o755))
+            .unwrap();
+        create_file_at(&ctx.client, "/cargo-test/src/c.txt", "Moved\n");
+        assert_eq!(
+            ctx.client
+                .move_path(
+                    "/cargo-test/src",
+                    "/cargo-test/dir",
+                    SmbMoveOptions::default().overwrite(true)
+                )
+                .unwrap(),
+            SmbMoveStrategy::Rename
+        );
+        assert_eq!(
+            read_file_at(&ctx.client, "/cargo-test/dir/c.txt"),
+            b"Moved\n"
+        );
+        let mut names: Vec<String> = ctx
+            .client
+            .list_dir("/cargo-test")
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["b.txt", "dir"]);
+        finalize_ctx(ctx);
+    }
+
+    #[test]
+    #[serial]
+    fn should_move_across_shares_with_copy() {
+        mock::logger();
+        let ctx = init_ctx();
+        create_walk_tree(&ctx.client);
+        let modified = ctx.client.stat("/cargo-test/a/d.txt").unwrap().modified;
+        let target = SmbClient::new(
+            SmbCredentials::default()
+                .server(&ctx.client.server)
+                .share("/other")
+                .username("test")
+                .password("test")
+                .workgroup("pavao"),
+            SmbOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            ctx.client
+                .move_to(
+                    "/cargo-test/a",
+                    &target,
+                    "/moved",
+                    SmbMoveOptions::default()
+                )
+                .unwrap(),
+            SmbMoveStrategy::CopyDelete
+        );
+        assert!(ctx.client.stat("/cargo-test/a").is_err());
+        assert_eq!(target.stat("/moved/d.txt").unwrap().modified, modified);
+        assert!(target.stat("/moved/b/c.txt").is_ok());
+        // without fallback, moving across shares fails
+        assert!(ctx
+            .client
+            .move_to(
+                "/cargo-test/e.txt",
+                &target,
+                "/e.txt",
+                SmbMoveOptions::default().copy_fallback(false)
+            )
+            .is_err());
+        target.remove_dir_all("/moved").unwrap();
+        // a failed copy leaves nothing at the destination and the source untouched
+        create_walk_tree(&ctx.client);
+        ctx.exec("chmod 000 /mnt/tmp/cargo-test/a/b/c.txt");
+        assert!(ctx
+            .client
+            .move_to(
+                "/cargo-test/a",
+                &target,
+                "/moved",
+                SmbMoveOptions::default()
+            )
+            .is_err());
+        assert!(target.stat("/moved").is_err());
+        assert!(ctx.client.stat("/cargo-test/a/d.txt").is_ok());
+        ctx.exec("chmod 644 /mnt/tmp/cargo-test/a/b/c.txt");
+        finalize_ctx(ctx);
+    }
+
+    /// Watch `/cargo-test` while `script` runs on the server;
+    /// returns whether `expected` has been reported within 10 seconds
+    fn watch_for(ctx: &TestCtx, script: &str, expected: SmbWatchEvent) -> bool {
+        let mut watch = ctx
+            .client
+            .watch(
+                "/cargo-test",
+                SmbWatchOptions::default().poll_interval(Duration::from_millis(100)),
+            )
+            .unwrap();
+        let stopper = watch.stopper();
+        let (done, wait) = std::sync::mpsc::channel::<()>();
+        std::thread::scope(|s| {
+            s.spawn(move || {
+                // let the watch register on the server first
+                std::thread::sleep(Duration::from_millis(500));
+                ctx.exec(script);
+                let _ = wait.recv_timeout(Duration::from_secs(10));
+                stopper.stop();
+            });
+            let found = watch.any(|event| event.ok().as_ref() == Some(&expected));
+            let _ = done.send(());
+            found
+        })
+    }
+
     fn init_ctx() -> TestCtx {
         TestCtx::default()
     }
@@ -861,4 +3629,22 @@ mod test {
             .expect("failed to open file");
         assert!(std::io::copy(&mut reader, &mut writer).is_ok());
     }
+
+    fn create_walk_tree(client: &SmbClient) {
+        assert!(client
+            .create_dir_all("/cargo-test/a/b", SmbMode::from(0o755))
+            .is_ok());
+        create_file_at(client, "/cargo-test/a/b/c.txt", "Hello, World!\n");
+        create_file_at(client, "/cargo-test/a/d.txt", "Hello, World!\n");
+        create_file_at(client, "/cargo-test/e.txt", "Hello, World!\n");
+    }
+
+    fn read_file_at<S: AsRef<str>>(client: &SmbClient, uri: S) -> Vec<u8> {
+        let mut reader = client
+            .open_with(uri, SmbOpenOptions::default().read(true))
+            .expect("failed to open file");
+        let mut output = Vec::new();
+        assert!(reader.read_to_end(&mut output).is_ok());
+        output
+    }
 }
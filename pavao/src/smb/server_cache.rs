@@ -0,0 +1,152 @@
+//! # ServerCache
+//!
+//! a static structure which tracks the server connections cached by libsmbclient,
+//! to enforce the idle timeout and the maximum number of cached connections
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use pavao_sys::{smbc_add_cached_srv_fn, smbc_get_cached_srv_fn, smbc_remove_cached_srv_fn};
+
+use crate::SmbCacheStats;
+
+#[derive(Debug, Default)]
+pub struct ServerCache {
+    /// libsmbclient cache functions, wrapped by the client
+    pub add_fn: smbc_add_cached_srv_fn,
+    pub get_fn: smbc_get_cached_srv_fn,
+    pub remove_fn: smbc_remove_cached_srv_fn,
+    idle_timeout: Option<Duration>,
+    max_servers: Option<usize>,
+    /// Last use of the cached servers, by address
+    servers: HashMap<usize, Instant>,
+    stats: SmbCacheStats,
+}
+
+impl ServerCache {
+    pub fn new(idle_timeout: Option<Duration>, max_servers: Option<usize>) -> Self {
+        Self {
+            idle_timeout,
+            max_servers,
+            ..Default::default()
+        }
+    }
+
+    pub fn added(&mut self, srv: usize, now: Instant) {
+        trace!("cached server {:#x}", srv);
+        self.servers.insert(srv, now);
+        self.stats.added += 1;
+    }
+
+    pub fn hit(&mut self, srv: usize, now: Instant) {
+        self.servers.insert(srv, now);
+        self.stats.hits += 1;
+    }
+
+    pub fn missed(&mut self) {
+        self.stats.misses += 1;
+    }
+
+    pub fn removed(&mut self, srv: usize) {
+        trace!("removed cached server {:#x}", srv);
+        if self.servers.remove(&srv).is_some() {
+            self.stats.removed += 1;
+        }
+    }
+
+    pub fn evicted(&mut self) {
+        self.stats.evicted += 1;
+    }
+
+    /// Servers idle for longer than the idle timeout
+    pub fn expired(&self, now: Instant) -> Vec<usize> {
+        let Some(timeout) = self.idle_timeout else {
+            return Vec::new();
+        };
+        self.servers
+            .iter()
+            .filter(|(_, last_use)| now.saturating_duration_since(**last_use) > timeout)
+            .map(|(srv, _)| *srv)
+            .collect()
+    }
+
+    /// Least recently used servers exceeding the maximum number of cached servers
+    pub fn excess(&self) -> Vec<usize> {
+        let Some(max) = self.max_servers else {
+            return Vec::new();
+        };
+        let mut servers: Vec<(&usize, &Instant)> = self.servers.iter().collect();
+        servers.sort_by_key(|(_, last_use)| **last_use);
+        let excess = servers.len().saturating_sub(max);
+        servers
+            .into_iter()
+            .take(excess)
+            .map(|(srv, _)| *srv)
+            .collect()
+    }
+
+    pub fn stats(&self) -> SmbCacheStats {
+        SmbCacheStats {
+            cached: self.servers.len(),
+            ..self.stats
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_count_cached_servers() {
+        let mut cache = ServerCache::default();
+        let now = Instant::now();
+        cache.missed();
+        cache.added(1, now);
+        cache.hit(1, now);
+        cache.missed();
+        cache.added(2, now);
+        cache.removed(1);
+        cache.removed(3);
+        cache.evicted();
+        assert_eq!(
+            cache.stats(),
+            SmbCacheStats {
+                cached: 1,
+                hits: 1,
+                misses: 2,
+                added: 2,
+                removed: 1,
+                evicted: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn should_find_expired_servers() {
+        let now = Instant::now();
+        let mut cache = ServerCache::new(Some(Duration::from_secs(60)), None);
+        cache.added(1, now);
+        cache.added(2, now + Duration::from_secs(30));
+        assert!(cache.expired(now + Duration::from_secs(60)).is_empty());
+        assert_eq!(cache.expired(now + Duration::from_secs(61)), vec![1]);
+        assert!(ServerCache::default()
+            .expired(now + Duration::from_secs(3600))
+            .is_empty());
+    }
+
+    #[test]
+    fn should_find_excess_servers() {
+        let now = Instant::now();
+        let mut cache = ServerCache::new(None, Some(2));
+        cache.added(1, now);
+        cache.added(2, now + Duration::from_secs(1));
+        assert!(cache.excess().is_empty());
+        cache.added(3, now + Duration::from_secs(2));
+        cache.hit(1, now + Duration::from_secs(3));
+        assert_eq!(cache.excess(), vec![2]);
+    }
+}
@@ -0,0 +1,85 @@
+//! # Symbols
+//!
+//! libsmbclient functions which are missing from older versions of the library.
+//!
+//! When linking dynamically they are resolved at runtime, so that the crate still links against
+//! an older libsmbclient; the vendored library is recent enough to call them directly
+
+use pavao_sys::{smbc_bool, smbc_readdirplus2_fn, SMBCCTX};
+
+#[cfg(not(feature = "vendored"))]
+mod imp {
+
+    use std::ffi::CStr;
+    use std::mem;
+
+    use pavao_sys::{smbc_bool, smbc_readdirplus2_fn, SMBCCTX};
+
+    type SetOptionBoolFn = unsafe extern "C" fn(*mut SMBCCTX, smbc_bool);
+    type GetOptionBoolFn = unsafe extern "C" fn(*mut SMBCCTX) -> smbc_bool;
+    type GetFunctionReaddirPlus2Fn = unsafe extern "C" fn(*mut SMBCCTX) -> smbc_readdirplus2_fn;
+
+    /// Look up the symbol `name` in the loaded libraries
+    fn lookup(name: &CStr) -> Option<*mut libc::c_void> {
+        let sym = unsafe { libc::dlsym(libc::RTLD_DEFAULT, name.as_ptr()) };
+        if sym.is_null() {
+            debug!("{:?} is not available in libsmbclient", name);
+            None
+        } else {
+            Some(sym)
+        }
+    }
+
+    pub unsafe fn set_option_posix_extensions(ctx: *mut SMBCCTX, b: smbc_bool) -> Option<()> {
+        let sym = lookup(c"smbc_setOptionPosixExtensions")?;
+        let set_fn: SetOptionBoolFn = mem::transmute(sym);
+        set_fn(ctx, b);
+        Some(())
+    }
+
+    pub unsafe fn get_option_posix_extensions(ctx: *mut SMBCCTX) -> Option<smbc_bool> {
+        let sym = lookup(c"smbc_getOptionPosixExtensions")?;
+        let get_fn: GetOptionBoolFn = mem::transmute(sym);
+        Some(get_fn(ctx))
+    }
+
+    pub unsafe fn get_function_readdirplus2(ctx: *mut SMBCCTX) -> smbc_readdirplus2_fn {
+        let sym = lookup(c"smbc_getFunctionReaddirPlus2")?;
+        let get_fn: GetFunctionReaddirPlus2Fn = mem::transmute(sym);
+        get_fn(ctx)
+    }
+}
+
+#[cfg(feature = "vendored")]
+mod imp {
+
+    use pavao_sys::{smbc_bool, smbc_readdirplus2_fn, SMBCCTX};
+
+    pub unsafe fn set_option_posix_extensions(ctx: *mut SMBCCTX, b: smbc_bool) -> Option<()> {
+        pavao_sys::smbc_setOptionPosixExtensions(ctx, b);
+        Some(())
+    }
+
+    pub unsafe fn get_option_posix_extensions(ctx: *mut SMBCCTX) -> Option<smbc_bool> {
+        Some(pavao_sys::smbc_getOptionPosixExtensions(ctx))
+    }
+
+    pub unsafe fn get_function_readdirplus2(ctx: *mut SMBCCTX) -> smbc_readdirplus2_fn {
+        pavao_sys::smbc_getFunctionReaddirPlus2(ctx)
+    }
+}
+
+/// Request the SMB3.1.1 POSIX extensions; `None` if libsmbclient doesn't support them
+pub unsafe fn set_option_posix_extensions(ctx: *mut SMBCCTX, b: smbc_bool) -> Option<()> {
+    imp::set_option_posix_extensions(ctx, b)
+}
+
+/// Get whether the SMB3.1.1 POSIX extensions are requested; `None` if libsmbclient doesn't support them
+pub unsafe fn get_option_posix_extensions(ctx: *mut SMBCCTX) -> Option<smbc_bool> {
+    imp::get_option_posix_extensions(ctx)
+}
+
+/// Get the `readdirplus2` function of the context; `None` if libsmbclient doesn't support it
+pub unsafe fn get_function_readdirplus2(ctx: *mut SMBCCTX) -> smbc_readdirplus2_fn {
+    imp::get_function_readdirplus2(ctx)
+}
@@ -0,0 +1,107 @@
+//! # Attributes
+//!
+//! module which exposes the DOS file attributes
+
+use bitflags::bitflags;
+
+use crate::SmbError;
+
+bitflags! {
+    /// DOS attributes of a file, as the `FILE_ATTRIBUTE_*` constants defined in MS-FSCC
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct SmbFileAttributes: u32 {
+        const READONLY = 0x0000_0001;
+        const HIDDEN = 0x0000_0002;
+        const SYSTEM = 0x0000_0004;
+        const VOLUME = 0x0000_0008;
+        const DIRECTORY = 0x0000_0010;
+        const ARCHIVE = 0x0000_0020;
+        const DEVICE = 0x0000_0040;
+        const NORMAL = 0x0000_0080;
+        const TEMPORARY = 0x0000_0100;
+        const SPARSE_FILE = 0x0000_0200;
+        const REPARSE_POINT = 0x0000_0400;
+        const COMPRESSED = 0x0000_0800;
+        const OFFLINE = 0x0000_1000;
+        const NOT_CONTENT_INDEXED = 0x0000_2000;
+        const ENCRYPTED = 0x0000_4000;
+        const INTEGRITY_STREAM = 0x0000_8000;
+        const VIRTUAL = 0x0001_0000;
+        const NO_SCRUB_DATA = 0x0002_0000;
+        const RECALL_ON_OPEN = 0x0004_0000;
+        const PINNED = 0x0008_0000;
+        const UNPINNED = 0x0010_0000;
+        const RECALL_ON_DATA_ACCESS = 0x0040_0000;
+    }
+}
+
+impl SmbFileAttributes {
+    /// Parse attributes from the value of the `system.dos_attr.mode` xattr (e.g. `0x20`)
+    pub(crate) fn from_xattr(value: &str) -> Result<Self, SmbError> {
+        let value = value.trim();
+        match value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+        {
+            Some(hex) => u32::from_str_radix(hex, 16),
+            None => value.parse::<u32>(),
+        }
+        .map(Self::from_bits_retain)
+        .map_err(|_| SmbError::BadValue)
+    }
+
+    /// Format attributes as a value for the `system.dos_attr.mode` xattr
+    pub(crate) fn to_xattr(self) -> String {
+        format!("0x{:x}", self.bits())
+    }
+}
+
+impl From<u16> for SmbFileAttributes {
+    fn from(attrs: u16) -> Self {
+        Self::from_bits_retain(attrs as u32)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_attributes_from_xattr() {
+        assert_eq!(
+            SmbFileAttributes::from_xattr("0x21").unwrap(),
+            SmbFileAttributes::READONLY | SmbFileAttributes::ARCHIVE
+        );
+        assert_eq!(
+            SmbFileAttributes::from_xattr("16").unwrap(),
+            SmbFileAttributes::DIRECTORY
+        );
+        assert_eq!(
+            SmbFileAttributes::from_xattr("0x400").unwrap(),
+            SmbFileAttributes::REPARSE_POINT
+        );
+        assert!(SmbFileAttributes::from_xattr("foo").is_err());
+    }
+
+    #[test]
+    fn should_format_attributes_as_xattr() {
+        assert_eq!(
+            (SmbFileAttributes::HIDDEN | SmbFileAttributes::SYSTEM).to_xattr(),
+            "0x6"
+        );
+        assert_eq!(SmbFileAttributes::empty().to_xattr(), "0x0");
+    }
+
+    #[test]
+    fn should_convert_u16_to_attributes() {
+        assert_eq!(
+            SmbFileAttributes::from(0x4810u16),
+            SmbFileAttributes::DIRECTORY
+                | SmbFileAttributes::COMPRESSED
+                | SmbFileAttributes::ENCRYPTED
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! # Browse
+//!
+//! records returned when browsing the network with `list_shares`, `list_servers` and `list_workgroups`
+
+use super::{SmbDirent, SmbDirentType};
+
+/// Type of a share exposed by a server
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SmbShareType {
+    /// Disk share
+    Disk,
+    /// Print queue
+    Printer,
+    /// Communication device
+    Comms,
+    /// Interprocess communication share (e.g. `IPC$`)
+    Ipc,
+}
+
+impl SmbShareType {
+    fn from_dirent_type(type_: SmbDirentType) -> Option<Self> {
+        match type_ {
+            SmbDirentType::FileShare => Some(Self::Disk),
+            SmbDirentType::PrinterShare => Some(Self::Printer),
+            SmbDirentType::CommsShare => Some(Self::Comms),
+            SmbDirentType::IpcShare => Some(Self::Ipc),
+            _ => None,
+        }
+    }
+}
+
+/// Share exposed by a server
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SmbShare {
+    pub name: String,
+    pub share_type: SmbShareType,
+    pub comment: String,
+}
+
+impl SmbShare {
+    /// Build a share from a dirent; returns `None` if the dirent is not a share
+    pub(crate) fn from_dirent(dirent: &SmbDirent) -> Option<Self> {
+        SmbShareType::from_dirent_type(dirent.get_type()).map(|share_type| Self {
+            name: dirent.name().to_string(),
+            share_type,
+            comment: dirent.comment().to_string(),
+        })
+    }
+
+    /// Returns whether the share is hidden (its name ends with `$`)
+    pub fn is_hidden(&self) -> bool {
+        self.name.ends_with('$')
+    }
+}
+
+/// Server in a workgroup
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SmbServer {
+    pub name: String,
+    pub comment: String,
+}
+
+impl SmbServer {
+    /// Build a server from a dirent; returns `None` if the dirent is not a server
+    pub(crate) fn from_dirent(dirent: &SmbDirent) -> Option<Self> {
+        (dirent.get_type() == SmbDirentType::Server).then(|| Self {
+            name: dirent.name().to_string(),
+            comment: dirent.comment().to_string(),
+        })
+    }
+}
+
+/// Workgroup or domain on the network
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SmbWorkgroup {
+    pub name: String,
+    pub comment: String,
+}
+
+impl SmbWorkgroup {
+    /// Build a workgroup from a dirent; returns `None` if the dirent is not a workgroup
+    pub(crate) fn from_dirent(dirent: &SmbDirent) -> Option<Self> {
+        (dirent.get_type() == SmbDirentType::Workgroup).then(|| Self {
+            name: dirent.name().to_string(),
+            comment: dirent.comment().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use libc::c_uint;
+    use pavao_sys::smbc_dirent;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::utils;
+
+    fn dirent(type_: SmbDirentType, name: &str, comment: &str) -> SmbDirent {
+        let mut dirent = smbc_dirent {
+            smbc_type: c_uint::from(type_),
+            comment: utils::str_to_cstring(comment).unwrap().into_raw(),
+            ..Default::default()
+        };
+        for (i, c) in name.bytes().enumerate() {
+            dirent.name[i] = c as libc::c_char;
+        }
+        SmbDirent::try_from(dirent).unwrap()
+    }
+
+    #[test]
+    fn should_build_share_from_dirent() {
+        assert_eq!(
+            SmbShare::from_dirent(&dirent(SmbDirentType::FileShare, "temp", "Temp share")).unwrap(),
+            SmbShare {
+                name: "temp".to_string(),
+                share_type: SmbShareType::Disk,
+                comment: "Temp share".to_string(),
+            }
+        );
+        let ipc = SmbShare::from_dirent(&dirent(SmbDirentType::IpcShare, "IPC$", "")).unwrap();
+        assert_eq!(ipc.share_type, SmbShareType::Ipc);
+        assert!(ipc.is_hidden());
+        assert!(SmbShare::from_dirent(&dirent(SmbDirentType::Dir, "dir", "")).is_none());
+    }
+
+    #[test]
+    fn should_build_server_and_workgroup_from_dirent() {
+        assert_eq!(
+            SmbServer::from_dirent(&dirent(SmbDirentType::Server, "NAS", "Samba")).unwrap(),
+            SmbServer {
+                name: "NAS".to_string(),
+                comment: "Samba".to_string(),
+            }
+        );
+        assert!(SmbServer::from_dirent(&dirent(SmbDirentType::Workgroup, "WG", "")).is_none());
+        assert_eq!(
+            SmbWorkgroup::from_dirent(&dirent(SmbDirentType::Workgroup, "WG", "")).unwrap(),
+            SmbWorkgroup {
+                name: "WG".to_string(),
+                comment: String::new(),
+            }
+        );
+    }
+}
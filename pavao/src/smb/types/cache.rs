@@ -0,0 +1,20 @@
+//! # Cache
+//!
+//! statistics of the server connections cached by libsmbclient
+
+/// Statistics of the server connections cached by libsmbclient, since the context has been created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SmbCacheStats {
+    /// Connections currently cached
+    pub cached: usize,
+    /// Lookups which reused a cached connection
+    pub hits: u64,
+    /// Lookups which found no cached connection, so a new one had to be opened
+    pub misses: u64,
+    /// Connections added to the cache
+    pub added: u64,
+    /// Connections removed from the cache, including the evicted ones
+    pub removed: u64,
+    /// Connections closed because they were idle for too long or exceeded the cache size
+    pub evicted: u64,
+}
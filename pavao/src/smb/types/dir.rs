@@ -0,0 +1,161 @@
+//! # Dir
+//!
+//! directory iterator returned by `read_dir`
+
+use libc::off_t;
+use pavao_sys::{
+    smbc_getFunctionClosedir, smbc_getFunctionLseekdir, smbc_getFunctionReaddir,
+    smbc_getFunctionTelldir, SMBCFILE,
+};
+
+use crate::{utils, SmbClient, SmbDirent, SmbError, SmbResult};
+
+/// Lazy iterator over the entries of a directory.
+///
+/// Entries are read from the server while iterating; `.` and `..` are skipped.
+/// A failure to read the directory is yielded as an [`SmbError::Io`].
+/// The directory handle is closed when the iterator is dropped.
+pub struct SmbReadDir<'a> {
+    smbc: &'a SmbClient,
+    fd: *mut SMBCFILE,
+}
+
+/// Options for `remove_dir_all_with`
+#[derive(Debug, Clone, Copy)]
+pub struct SmbRemoveDirOptions {
+    pub(crate) clear_readonly: bool,
+    pub(crate) continue_on_error: bool,
+}
+
+impl Default for SmbRemoveDirOptions {
+    fn default() -> Self {
+        Self {
+            clear_readonly: true,
+            continue_on_error: false,
+        }
+    }
+}
+
+impl SmbRemoveDirOptions {
+    /// Clear the read-only attribute of entries before removing them (default: `true`)
+    pub fn clear_readonly(mut self, clear_readonly: bool) -> Self {
+        self.clear_readonly = clear_readonly;
+        self
+    }
+
+    /// Keep removing the other entries when an entry can't be removed,
+    /// collecting all the errors (default: `false`)
+    pub fn continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.continue_on_error = continue_on_error;
+        self
+    }
+}
+
+/// Position in a directory listing, returned by [`SmbReadDir::tell`].
+///
+/// A position is only meaningful for the [`SmbReadDir`] it has been obtained from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmbDirPosition(off_t);
+
+impl<'a> SmbReadDir<'a> {
+    pub(crate) fn new(smbc: &'a SmbClient, fd: *mut SMBCFILE) -> Self {
+        Self { smbc, fd }
+    }
+
+    /// Get the current position in the listing,
+    /// which can be later restored with [`SmbReadDir::seek`] to resume the listing
+    pub fn tell(&self) -> SmbResult<SmbDirPosition> {
+        trace!("getting directory position");
+        let ctx = self.smbc.ctx()?;
+        let telldir_fn = self.smbc.get_fn(ctx, smbc_getFunctionTelldir)?;
+        utils::to_result_with_le(telldir_fn(ctx, self.fd))
+            .map(SmbDirPosition)
+            .map_err(SmbError::from)
+    }
+
+    /// Restore the listing to `pos`, previously obtained with [`SmbReadDir::tell`]
+    pub fn seek(&mut self, pos: SmbDirPosition) -> SmbResult<()> {
+        trace!("seeking directory to {:?}", pos);
+        let ctx = self.smbc.ctx()?;
+        let lseekdir_fn = self.smbc.get_fn(ctx, smbc_getFunctionLseekdir)?;
+        utils::to_result_with_ioerror((), lseekdir_fn(ctx, self.fd, pos.0))
+    }
+}
+
+impl Iterator for SmbReadDir<'_> {
+    type Item = SmbResult<SmbDirent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ctx = match self.smbc.ctx() {
+            Ok(ctx) => ctx,
+            Err(e) => return Some(Err(e)),
+        };
+        let readdir_fn = match self.smbc.get_fn(ctx, smbc_getFunctionReaddir) {
+            Ok(readdir_fn) => readdir_fn,
+            Err(e) => return Some(Err(e.into())),
+        };
+        loop {
+            // a null entry is both the end of the directory and an error; only an error sets errno
+            utils::clear_errno();
+            let dirent = readdir_fn(ctx, self.fd);
+            if dirent.is_null() {
+                return match utils::last_errno() {
+                    0 => None,
+                    errno => {
+                        error!("failed to read directory: {}", utils::last_os_error());
+                        Some(Err(std::io::Error::from_raw_os_error(errno).into()))
+                    }
+                };
+            }
+            match SmbDirent::try_from(unsafe { *dirent }) {
+                Ok(dirent)
+                    if dirent.name() == "."
+                        || dirent.name() == ".."
+                        || dirent.name().is_empty() =>
+                {
+                    trace!("ignoring '..', '.' directories");
+                }
+                Ok(dirent) => {
+                    trace!("found dirent: {:?}", dirent);
+                    return Some(Ok(dirent));
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl Drop for SmbReadDir<'_> {
+    fn drop(&mut self) {
+        trace!("closing directory");
+        if let Ok(ctx) = self.smbc.ctx() {
+            if let Ok(closedir_fn) = self.smbc.get_fn(ctx, smbc_getFunctionClosedir) {
+                closedir_fn(ctx, self.fd);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_initialize_remove_dir_options() {
+        let options = SmbRemoveDirOptions::default();
+        assert_eq!(options.clear_readonly, true);
+        assert_eq!(options.continue_on_error, false);
+    }
+
+    #[test]
+    fn should_configure_remove_dir_options() {
+        let options = SmbRemoveDirOptions::default()
+            .clear_readonly(false)
+            .continue_on_error(true);
+        assert_eq!(options.clear_readonly, false);
+        assert_eq!(options.continue_on_error, true);
+    }
+}
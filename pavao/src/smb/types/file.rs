@@ -6,11 +6,11 @@ use std::io::{self, Read, Seek, SeekFrom, Write};
 
 use libc::{c_int, c_void, mode_t, off_t};
 use pavao_sys::{
-    smbc_getFunctionClose, smbc_getFunctionLseek, smbc_getFunctionRead, smbc_getFunctionWrite,
-    SMBCFILE,
+    smbc_getFunctionClose, smbc_getFunctionFtruncate, smbc_getFunctionLseek, smbc_getFunctionRead,
+    smbc_getFunctionWrite, SMBCFILE,
 };
 
-use crate::{utils, SmbClient};
+use crate::{utils, SmbClient, SmbResult};
 
 pub struct SmbFile<'a> {
     smbc: &'a SmbClient,
@@ -21,6 +21,33 @@ impl<'a> SmbFile<'a> {
     pub(crate) fn new(smbc: &'a SmbClient, fd: *mut SMBCFILE) -> Self {
         Self { smbc, fd }
     }
+
+    /// Get the underlying file descriptor
+    pub(crate) fn fd(&self) -> *mut SMBCFILE {
+        self.fd
+    }
+
+    /// Truncates or extends the underlying file, updating its size to `size`.
+    ///
+    /// If `size` is less than the current file size, the file is shrunk;
+    /// if it is greater, the file is extended and the new bytes are filled with zeros.
+    /// As for [`std::fs::File::set_len`], the file cursor is left unchanged.
+    pub fn set_len(&self, size: u64) -> SmbResult<()> {
+        trace!("setting file length to {}", size);
+        let size = off_t::try_from(size).map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+        let ctx = self.smbc.ctx()?;
+        let ftruncate_fn = self.smbc.get_fn(ctx, smbc_getFunctionFtruncate)?;
+        utils::to_result_with_ioerror((), ftruncate_fn(ctx, self.fd, size))
+    }
+
+    /// Close the file, returning the error reported by the server
+    pub(crate) fn close(mut self) -> SmbResult<()> {
+        trace!("closing file");
+        let fd = std::mem::replace(&mut self.fd, std::ptr::null_mut());
+        let ctx = self.smbc.ctx()?;
+        let close_fn = self.smbc.get_fn(ctx, smbc_getFunctionClose)?;
+        utils::to_result_with_ioerror((), close_fn(ctx, fd))
+    }
 }
 
 impl Read for SmbFile<'_> {
@@ -85,6 +112,9 @@ impl Seek for SmbFile<'_> {
 
 impl Drop for SmbFile<'_> {
     fn drop(&mut self) {
+        if self.fd.is_null() {
+            return;
+        }
         trace!("closing file");
         if let Ok(ctx) = self.smbc.ctx() {
             if let Ok(close_fn) = self.smbc.get_fn(ctx, smbc_getFunctionClose) {
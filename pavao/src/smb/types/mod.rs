@@ -2,16 +2,49 @@
 //!
 //! smb types
 
+mod attributes;
+mod browse;
+mod cache;
 mod credentials;
+mod dir;
 mod dirent;
 mod file;
 mod mode;
 mod options;
+mod print;
+mod rename;
+mod security;
+mod session;
+mod snapshot;
 mod stat;
+pub(crate) mod stream;
+pub(crate) mod transfer;
+mod walk;
+mod watch;
+mod xattr;
 
+pub use attributes::SmbFileAttributes;
+pub use browse::{SmbServer, SmbShare, SmbShareType, SmbWorkgroup};
+pub use cache::SmbCacheStats;
 pub use credentials::SmbCredentials;
+pub use dir::{SmbDirPosition, SmbReadDir, SmbRemoveDirOptions};
 pub use dirent::{SmbDirent, SmbDirentType};
 pub use file::{SmbFile, SmbOpenOptions};
 pub use mode::{SmbMode, SmbModeClass};
-pub use options::{SmbEncryptionLevel, SmbOptions, SmbShareMode};
-pub use stat::{SmbDirentInfo, SmbStat, SmbStatVfs};
+pub use options::{
+    SmbAddressFamily, SmbEffectiveOptions, SmbEncryptionLevel, SmbNameResolver, SmbOptions,
+    SmbProtocol, SmbShareMode, SmbSigningPolicy, SmbTransport,
+};
+pub use print::{SmbPrintJob, SmbPrintJobInfo};
+pub use rename::{SmbMoveOptions, SmbMoveStrategy};
+pub use security::{
+    SmbAccessMask, SmbAce, SmbAceFlags, SmbAceType, SmbSecurityDescriptor, SmbTrustee,
+};
+pub use session::SmbSessionSecurity;
+pub use snapshot::SmbSnapshot;
+pub use stat::{SmbDirentInfo, SmbDirentStat, SmbStat, SmbStatVfs};
+pub use stream::SmbStreamInfo;
+pub use transfer::{SmbTransferOptions, SmbTransferProgress, SmbTransferSummary};
+pub use walk::{SmbWalk, SmbWalkEntry, SmbWalkOrder};
+pub use watch::{SmbNotifyFilter, SmbWatch, SmbWatchEvent, SmbWatchOptions, SmbWatchStopper};
+pub use xattr::SmbXattrFlag;
@@ -2,19 +2,54 @@
 //!
 //! module which exposes the smb client options
 
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
 use pavao_sys::{smbc_share_mode, smbc_smb_encrypt_level};
 
+use crate::{SmbError, SmbResult};
+
+/// Port of SMB over direct TCP
+const DIRECT_TCP_PORT: u16 = 445;
+/// Port of SMB over NetBIOS
+const NETBIOS_PORT: u16 = 139;
+/// Domain of the UNC-safe form of IPv6 addresses, understood by Samba
+const IPV6_LITERAL_DOMAIN: &str = ".ipv6-literal.net";
+/// Default libsmbclient debug level; the deprecated `debug` feature raises it to 10
+const DEFAULT_DEBUG_LEVEL: i32 = if cfg!(feature = "debug") { 10 } else { 0 };
+/// smb.conf parameters which set the session security, normalized as by [`normalize_parameter`];
+/// they are set by the options only, so that the required session security can be told
+const SESSION_SECURITY_PARAMETERS: &[&str] =
+    &["clientsigning", "clientsmbencrypt", "clientprotection"];
+
 /// Smb connection options
 #[derive(Debug, Clone)]
 pub struct SmbOptions {
+    pub(crate) address_family: Option<SmbAddressFamily>,
     pub(crate) browser_max_lmb_count: i32,
     pub(crate) case_sensitive: bool,
+    pub(crate) config_file: Option<PathBuf>,
+    pub(crate) connection_idle_timeout: Option<Duration>,
+    pub(crate) debug_level: i32,
     pub(crate) encryption_level: SmbEncryptionLevel,
     pub(crate) fallback_after_kerberos: bool,
     pub(crate) full_time_names: bool,
+    pub(crate) max_cached_connections: Option<usize>,
+    pub(crate) max_protocol: Option<SmbProtocol>,
+    pub(crate) min_protocol: Option<SmbProtocol>,
+    pub(crate) name_resolve_order: Vec<SmbNameResolver>,
     pub(crate) no_auto_anonymous_login: bool,
     pub(crate) one_share_per_server: bool,
     pub(crate) open_share_mode: SmbShareMode,
+    pub(crate) parameters: BTreeMap<String, String>,
+    pub(crate) port: Option<u16>,
+    pub(crate) posix_extensions: bool,
+    pub(crate) signing: Option<SmbSigningPolicy>,
+    pub(crate) transport: Option<SmbTransport>,
     pub(crate) url_encode_readdir_entries: bool,
     pub(crate) use_ccache: bool,
     pub(crate) use_kerberos: bool,
@@ -23,14 +58,27 @@ pub struct SmbOptions {
 impl Default for SmbOptions {
     fn default() -> Self {
         Self {
+            address_family: None,
             browser_max_lmb_count: 0,
             case_sensitive: false,
+            config_file: None,
+            connection_idle_timeout: None,
+            debug_level: DEFAULT_DEBUG_LEVEL,
             encryption_level: SmbEncryptionLevel::None,
             fallback_after_kerberos: false,
             full_time_names: false,
+            max_cached_connections: None,
+            max_protocol: None,
+            min_protocol: None,
+            name_resolve_order: Vec::new(),
             no_auto_anonymous_login: false,
             one_share_per_server: false,
             open_share_mode: SmbShareMode::DenyNone,
+            parameters: BTreeMap::new(),
+            port: None,
+            posix_extensions: false,
+            signing: None,
+            transport: None,
             url_encode_readdir_entries: false,
             use_ccache: false,
             use_kerberos: false,
@@ -39,6 +87,25 @@ impl Default for SmbOptions {
 }
 
 impl SmbOptions {
+    /// Prefer the addresses of `family` when the server name resolves to both IPv4 and IPv6 addresses
+    /// (default: libsmbclient tries all the addresses).
+    ///
+    /// libsmbclient has no such setting, so the server name is resolved by the system resolver when the client is created,
+    /// and the preferred address is used in the URLs instead; if the name can't be resolved, it is left to libsmbclient.
+    ///
+    /// **Limitations**: since the server is then reached by address, not by name:
+    ///
+    /// - Kerberos can't be used, as the service ticket is requested for the server name,
+    ///   so this can't be combined with `use_kerberos`;
+    /// - the NetBIOS called name is the address, which servers may refuse,
+    ///   so this can't be combined with the NetBIOS transport;
+    /// - the address is resolved once, so changes of the server address are not followed
+    ///   until a new client context is created
+    pub fn address_family(mut self, family: SmbAddressFamily) -> Self {
+        self.address_family = Some(family);
+        self
+    }
+
     pub fn browser_max_lmb_count(mut self, browser_max_lmb_count: i32) -> Self {
         self.browser_max_lmb_count = browser_max_lmb_count;
         self
@@ -49,6 +116,32 @@ impl SmbOptions {
         self
     }
 
+    /// Load the smb.conf at `path` in the client context (default: libsmbclient default configuration).
+    ///
+    /// The file can't set the session security (`client signing`, `client smb encrypt` and `client protection`),
+    /// which is set by [`SmbOptions::signing`] and [`SmbOptions::encryption_level`]; creating the client fails if it does
+    pub fn config_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.config_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Close the cached server connections which have not been used for longer than `timeout` (default: never).
+    ///
+    /// libsmbclient has no timer, so idle connections are closed on the next operation;
+    /// connections with open files or directories are kept
+    pub fn connection_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.connection_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the libsmbclient debug level, from 0 (errors only) to 10 (default: 0, or 10 with the deprecated `debug` feature).
+    ///
+    /// libsmbclient messages are forwarded to the `log` crate with the `libsmbclient` target
+    pub fn debug_level(mut self, debug_level: i32) -> Self {
+        self.debug_level = debug_level;
+        self
+    }
+
     pub fn encryption_level(mut self, encryption_level: SmbEncryptionLevel) -> Self {
         self.encryption_level = encryption_level;
         self
@@ -59,11 +152,50 @@ impl SmbOptions {
         self
     }
 
+    /// Use the full names of the time xattrs, such as `system.dos_attr.create_time` (default: `false`).
+    ///
+    /// The create time is only exposed with full names, so `SmbClient::stat` reads the birth time only when it is set
     pub fn full_time_names(mut self, full_time_names: bool) -> Self {
         self.full_time_names = full_time_names;
         self
     }
 
+    /// Set the maximum number of server connections kept in the cache (default: unlimited).
+    ///
+    /// When exceeded, the least recently used connections are closed, unless they have open files or directories
+    pub fn max_cached_connections(mut self, max: usize) -> Self {
+        self.max_cached_connections = Some(max);
+        self
+    }
+
+    /// Set the highest protocol dialect which can be negotiated (default: libsmbclient default).
+    ///
+    /// libsmbclient doesn't report the dialect which has been negotiated;
+    /// see the limitations of [`SmbClient`](crate::SmbClient)
+    pub fn max_protocol(mut self, protocol: SmbProtocol) -> Self {
+        self.max_protocol = Some(protocol);
+        self
+    }
+
+    /// Set the lowest protocol dialect which can be negotiated (default: libsmbclient default).
+    ///
+    /// Set it to [`SmbProtocol::Smb2_02`] or higher to forbid SMB1.
+    /// libsmbclient doesn't report the dialect which has been negotiated;
+    /// see the limitations of [`SmbClient`](crate::SmbClient)
+    pub fn min_protocol(mut self, protocol: SmbProtocol) -> Self {
+        self.min_protocol = Some(protocol);
+        self
+    }
+
+    /// Set the methods used to resolve names, in order (default: libsmbclient default, usually `lmhosts wins host bcast`)
+    pub fn name_resolve_order<I>(mut self, order: I) -> Self
+    where
+        I: IntoIterator<Item = SmbNameResolver>,
+    {
+        self.name_resolve_order = order.into_iter().collect();
+        self
+    }
+
     pub fn no_auto_anonymous_login(mut self, no_auto_anonymous_login: bool) -> Self {
         self.no_auto_anonymous_login = no_auto_anonymous_login;
         self
@@ -79,6 +211,50 @@ impl SmbOptions {
         self
     }
 
+    /// Set the smb.conf client parameter `key` to `value` (e.g. `name resolve order`).
+    ///
+    /// Parameters override the ones set in the configuration file and the ones set by the other options;
+    /// unknown parameters and the session security parameters, which are set by [`SmbOptions::signing`]
+    /// and [`SmbOptions::encryption_level`], make the client creation fail
+    pub fn parameter<K: AsRef<str>, V: AsRef<str>>(mut self, key: K, value: V) -> Self {
+        self.parameters.insert(
+            key.as_ref().trim().to_string(),
+            value.as_ref().trim().to_string(),
+        );
+        self
+    }
+
+    /// Set the port used to connect to servers (default: 445, then 139).
+    ///
+    /// If the server URL has a port too, they must be the same
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Request the SMB3.1.1 POSIX extensions, to get real Unix modes, owners and case-sensitive names from the server.
+    ///
+    /// The extensions are only used if the server supports them; this implies `case_sensitive`.
+    /// If libsmbclient doesn't support them, creating the client fails.
+    /// Whether they have been negotiated isn't reported, and symlinks can't be created nor read;
+    /// see the limitations of [`SmbClient`](crate::SmbClient)
+    pub fn posix_extensions(mut self, posix_extensions: bool) -> Self {
+        self.posix_extensions = posix_extensions;
+        self
+    }
+
+    /// Set the signing policy of the client (default: libsmbclient default)
+    pub fn signing(mut self, signing: SmbSigningPolicy) -> Self {
+        self.signing = Some(signing);
+        self
+    }
+
+    /// Set the transport used to connect to servers (default: direct TCP, then NetBIOS)
+    pub fn transport(mut self, transport: SmbTransport) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
     pub fn url_encode_readdir_entries(mut self, url_encode_readdir_entries: bool) -> Self {
         self.url_encode_readdir_entries = url_encode_readdir_entries;
         self
@@ -95,8 +271,430 @@ impl SmbOptions {
     }
 }
 
+impl SmbOptions {
+    /// Check the options are consistent
+    pub(crate) fn validate(&self) -> SmbResult<()> {
+        if let (Some(min), Some(max)) = (self.min_protocol, self.max_protocol) {
+            if min > max {
+                return Err(SmbError::BadOption(format!(
+                    "min protocol {min} is higher than max protocol {max}"
+                )));
+            }
+        }
+        if self.address_family.is_some() && self.use_kerberos {
+            return Err(SmbError::BadOption(
+                "address family can't be used with kerberos, which needs the server name"
+                    .to_string(),
+            ));
+        }
+        if self.address_family.is_some() && self.transport == Some(SmbTransport::NetBios) {
+            return Err(SmbError::BadOption(
+                "address family can't be used with the NetBIOS transport, which needs the server name"
+                    .to_string(),
+            ));
+        }
+        match (self.port, self.transport) {
+            (Some(0), _) => return Err(SmbError::BadOption("port can't be 0".to_string())),
+            (Some(port), Some(SmbTransport::NetBios)) if port != NETBIOS_PORT => {
+                return Err(SmbError::BadOption(format!(
+                    "NetBIOS transport requires port {NETBIOS_PORT}, not {port}"
+                )));
+            }
+            (Some(NETBIOS_PORT), Some(SmbTransport::Direct)) => {
+                return Err(SmbError::BadOption(format!(
+                    "direct TCP transport can't use the NetBIOS port {NETBIOS_PORT}"
+                )));
+            }
+            _ => {}
+        }
+        if self.max_cached_connections == Some(0) {
+            return Err(SmbError::BadOption(
+                "max cached connections can't be 0".to_string(),
+            ));
+        }
+        for (i, resolver) in self.name_resolve_order.iter().enumerate() {
+            if self.name_resolve_order[..i].contains(resolver) {
+                return Err(SmbError::BadOption(format!(
+                    "name resolver {resolver} is set more than once"
+                )));
+            }
+        }
+        if let Some(path) = self.config_file.as_deref() {
+            if !path.is_file() {
+                return Err(SmbError::BadOption(format!(
+                    "configuration file {} not found",
+                    path.display()
+                )));
+            }
+            let conf = std::fs::read_to_string(path).map_err(|e| {
+                SmbError::BadOption(format!(
+                    "failed to read configuration file {}: {e}",
+                    path.display()
+                ))
+            })?;
+            if let Some(key) = Self::session_security_parameter(&conf) {
+                return Err(SmbError::BadOption(format!(
+                    "configuration file {} sets '{key}', which is set by the signing and encryption level options",
+                    path.display()
+                )));
+            }
+        }
+        for (key, value) in self.parameters.iter() {
+            if key.is_empty() || key.contains(['=', '[', ']']) || key.contains(char::is_control) {
+                return Err(SmbError::BadOption(format!("bad parameter name '{key}'")));
+            }
+            if SESSION_SECURITY_PARAMETERS.contains(&normalize_parameter(key).as_str()) {
+                return Err(SmbError::BadOption(format!(
+                    "parameter '{key}' is set by the signing and encryption level options"
+                )));
+            }
+            if value.contains(char::is_control) {
+                return Err(SmbError::BadOption(format!(
+                    "bad value for parameter '{key}'"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Find a session security parameter in the global section of the smb.conf `conf`
+    fn session_security_parameter(conf: &str) -> Option<&str> {
+        let mut global = true;
+        for line in conf.lines().map(str::trim) {
+            if line.starts_with('[') {
+                global = normalize_parameter(line) == "[global]";
+            } else if let Some((key, _)) = line.split_once('=') {
+                let key = key.trim();
+                if global
+                    && !key.starts_with(['#', ';'])
+                    && SESSION_SECURITY_PARAMETERS.contains(&normalize_parameter(key).as_str())
+                {
+                    return Some(key);
+                }
+            }
+        }
+        None
+    }
+
+    /// Render the smb.conf to load in the context, after the `config_file`; `None` if there's nothing to load
+    pub(crate) fn configuration(&self) -> Option<String> {
+        if self.signing.is_none()
+            && self.transport.is_none()
+            && self.name_resolve_order.is_empty()
+            && self.parameters.is_empty()
+        {
+            return None;
+        }
+        let mut conf = String::from("[global]\n");
+        if let Some(signing) = self.signing {
+            conf.push_str(&format!("\tclient signing = {}\n", signing.as_str()));
+        }
+        if let Some(transport) = self.transport {
+            conf.push_str(&format!("\tsmb ports = {}\n", transport.port()));
+        }
+        if !self.name_resolve_order.is_empty() {
+            let order: Vec<&str> = self.name_resolve_order.iter().map(|r| r.as_str()).collect();
+            conf.push_str(&format!("\tname resolve order = {}\n", order.join(" ")));
+        }
+        for (key, value) in self.parameters.iter() {
+            conf.push_str(&format!("\t{key} = {value}\n"));
+        }
+        Some(conf)
+    }
+
+    /// Get the server URL to use for `server`, checking its port against the `port` option
+    /// and replacing its name with the address of the preferred family
+    pub(crate) fn server_url(&self, server: &str) -> SmbResult<String> {
+        self.server_url_with(server, |host, port| {
+            (host, port).to_socket_addrs().map(|addrs| addrs.collect())
+        })
+    }
+
+    fn server_url_with<F>(&self, server: &str, resolve: F) -> SmbResult<String>
+    where
+        F: FnOnce(&str, u16) -> io::Result<Vec<SocketAddr>>,
+    {
+        let url = ServerUrl::parse(server)?;
+        if let (Some(url_port), Some(port)) = (url.port, self.port) {
+            if url_port != port {
+                return Err(SmbError::BadOption(format!(
+                    "server URL port {url_port} differs from port option {port}"
+                )));
+            }
+        }
+        match (url.port, self.transport) {
+            (Some(port), Some(SmbTransport::NetBios)) if port != NETBIOS_PORT => {
+                return Err(SmbError::BadOption(format!(
+                    "NetBIOS transport requires port {NETBIOS_PORT}, not server URL port {port}"
+                )));
+            }
+            (Some(NETBIOS_PORT), Some(SmbTransport::Direct)) => {
+                return Err(SmbError::BadOption(format!(
+                    "direct TCP transport can't use the NetBIOS port {NETBIOS_PORT} of the server URL"
+                )));
+            }
+            _ => {}
+        }
+        let Some(family) = self.address_family else {
+            return Ok(server.to_string());
+        };
+        if url.host.is_empty()
+            || url.host.starts_with('[')
+            || url.host.ends_with(IPV6_LITERAL_DOMAIN)
+            || url.host.parse::<IpAddr>().is_ok()
+        {
+            return Ok(server.to_string());
+        }
+        let port = url.port.or(self.port).unwrap_or(DIRECT_TCP_PORT);
+        let addrs = match resolve(url.host, port) {
+            Ok(addrs) => addrs,
+            Err(e) => {
+                debug!("could not resolve {}: {}", url.host, e);
+                return Ok(server.to_string());
+            }
+        };
+        match addrs.iter().find(|addr| family.matches(&addr.ip())) {
+            Some(addr) => {
+                let host = match addr {
+                    SocketAddr::V4(addr) => addr.ip().to_string(),
+                    SocketAddr::V6(addr) if addr.scope_id() != 0 => format!(
+                        "{}s{}{}",
+                        addr.ip().to_string().replace(':', "-"),
+                        addr.scope_id(),
+                        IPV6_LITERAL_DOMAIN
+                    ),
+                    SocketAddr::V6(addr) => format!(
+                        "{}{}",
+                        addr.ip().to_string().replace(':', "-"),
+                        IPV6_LITERAL_DOMAIN
+                    ),
+                };
+                debug!("using address {} for {}", host, url.host);
+                Ok(url.with_host(&host))
+            }
+            None => Ok(server.to_string()),
+        }
+    }
+}
+
+/// Server URL split into its parts: `{prefix}{host}[:{port}]{suffix}`
+struct ServerUrl<'a> {
+    prefix: &'a str,
+    host: &'a str,
+    port: Option<u16>,
+    suffix: &'a str,
+}
+
+impl<'a> ServerUrl<'a> {
+    fn parse(server: &'a str) -> SmbResult<Self> {
+        let scheme_len = server.find("://").map(|i| i + 3).unwrap_or(0);
+        let authority_len = server[scheme_len..]
+            .find('/')
+            .unwrap_or(server.len() - scheme_len);
+        let authority = &server[scheme_len..scheme_len + authority_len];
+        let userinfo_len = authority.rfind('@').map(|i| i + 1).unwrap_or(0);
+        let prefix = &server[..scheme_len + userinfo_len];
+        let suffix = &server[scheme_len + authority_len..];
+        let host_port = &authority[userinfo_len..];
+        // a port follows the last colon, unless it's part of a bare IPv6 address
+        let (host, port) = match host_port.rsplit_once(':') {
+            Some((host, port)) if !host.contains(':') || host.ends_with(']') => (host, Some(port)),
+            _ => (host_port, None),
+        };
+        let port = match port {
+            Some(port) => Some(
+                port.parse::<u16>()
+                    .map_err(|_| SmbError::BadOption(format!("bad port in server URL {server}")))?,
+            ),
+            None => None,
+        };
+        Ok(Self {
+            prefix,
+            host,
+            port,
+            suffix,
+        })
+    }
+
+    fn with_host(&self, host: &str) -> String {
+        match self.port {
+            Some(port) => format!("{}{}:{}{}", self.prefix, host, port, self.suffix),
+            None => format!("{}{}{}", self.prefix, host, self.suffix),
+        }
+    }
+}
+
+/// Transport used to connect to servers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmbTransport {
+    /// SMB over TCP on port 445
+    Direct,
+    /// SMB over NetBIOS session service on port 139
+    NetBios,
+}
+
+impl SmbTransport {
+    /// Port of the transport
+    fn port(&self) -> u16 {
+        match self {
+            Self::Direct => DIRECT_TCP_PORT,
+            Self::NetBios => NETBIOS_PORT,
+        }
+    }
+}
+
+/// Method used to resolve names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmbNameResolver {
+    /// The `lmhosts` file of Samba
+    Lmhosts,
+    /// WINS server
+    Wins,
+    /// System resolver (hosts file and DNS)
+    Host,
+    /// NetBIOS broadcast
+    Bcast,
+}
+
+impl SmbNameResolver {
+    /// Name of the resolver in the `name resolve order` parameter
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lmhosts => "lmhosts",
+            Self::Wins => "wins",
+            Self::Host => "host",
+            Self::Bcast => "bcast",
+        }
+    }
+}
+
+impl fmt::Display for SmbNameResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// IP address family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmbAddressFamily {
+    Ipv4,
+    Ipv6,
+}
+
+impl SmbAddressFamily {
+    fn matches(&self, ip: &IpAddr) -> bool {
+        match self {
+            Self::Ipv4 => ip.is_ipv4(),
+            Self::Ipv6 => ip.is_ipv6(),
+        }
+    }
+}
+
+/// Settings in effect on the client context, read back from libsmbclient.
+///
+/// All the clients share the same global context, which is configured with the [`SmbOptions`] of the first client created:
+/// the options passed to the later clients are ignored, so these may differ from them.
+/// The settings changed at runtime, such as with [`SmbClient::set_debug_level`], are changed for all the clients.
+/// The protocol range, the signing policy and the smb.conf parameters can't be read back from libsmbclient,
+/// so they are not reported
+///
+/// [`SmbClient::set_debug_level`]: crate::SmbClient::set_debug_level
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SmbEffectiveOptions {
+    /// Number of local master browsers queried to list workgroups; see [`SmbOptions::browser_max_lmb_count`]
+    pub browser_max_lmb_count: i32,
+    /// Whether paths are case sensitive; always on with the POSIX extensions
+    pub case_sensitive: bool,
+    /// libsmbclient debug level, which can be changed at runtime with [`SmbClient::set_debug_level`]
+    ///
+    /// [`SmbClient::set_debug_level`]: crate::SmbClient::set_debug_level
+    pub debug_level: i32,
+    /// Encryption level required on the sessions; see [`SmbOptions::encryption_level`]
+    pub encryption_level: SmbEncryptionLevel,
+    /// Whether NTLM is tried when Kerberos fails; see [`SmbOptions::fallback_after_kerberos`]
+    pub fallback_after_kerberos: bool,
+    /// Whether the full time names of the DOS attributes are reported; see [`SmbOptions::full_time_names`]
+    pub full_time_names: bool,
+    /// NetBIOS name of the client, from smb.conf or the host name unless changed with [`SmbClient::set_netbios_name`]
+    ///
+    /// [`SmbClient::set_netbios_name`]: crate::SmbClient::set_netbios_name
+    pub netbios_name: String,
+    /// Whether anonymous login is never tried; see [`SmbOptions::no_auto_anonymous_login`]
+    pub no_auto_anonymous_login: bool,
+    /// Whether a single connection is used for all the shares of a server; see [`SmbOptions::one_share_per_server`]
+    pub one_share_per_server: bool,
+    /// Share mode used to open files; see [`SmbOptions::open_share_mode`]
+    pub open_share_mode: SmbShareMode,
+    /// Port used to connect to servers; `None` if libsmbclient picks it
+    pub port: Option<u16>,
+    /// Whether the SMB3.1.1 POSIX extensions are requested, not whether the server negotiated them;
+    /// `false` if libsmbclient doesn't support them
+    pub posix_extensions: bool,
+    /// Timeout of the server requests, which can be changed at runtime with [`SmbClient::set_timeout`]
+    ///
+    /// [`SmbClient::set_timeout`]: crate::SmbClient::set_timeout
+    pub timeout: Duration,
+    /// Whether the names of the directory entries are URL encoded; see [`SmbOptions::url_encode_readdir_entries`]
+    pub url_encode_readdir_entries: bool,
+    /// Whether the Kerberos credentials cache is used; see [`SmbOptions::use_ccache`]
+    pub use_ccache: bool,
+    /// Whether Kerberos authentication is used; see [`SmbOptions::use_kerberos`]
+    pub use_kerberos: bool,
+    /// Default user name of the context, from the environment unless changed with [`SmbClient::set_user`].
+    ///
+    /// The user of the [`SmbCredentials`](crate::SmbCredentials) is given on authentication, so it isn't reported here
+    ///
+    /// [`SmbClient::set_user`]: crate::SmbClient::set_user
+    pub user: String,
+    /// Default workgroup of the context, from smb.conf unless changed with [`SmbClient::set_workgroup`].
+    ///
+    /// The workgroup of the [`SmbCredentials`](crate::SmbCredentials) is given on authentication, so it isn't reported here
+    ///
+    /// [`SmbClient::set_workgroup`]: crate::SmbClient::set_workgroup
+    pub workgroup: String,
+}
+
+/// SMB protocol dialect, from the oldest to the newest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SmbProtocol {
+    /// SMB1 (NT LM 0.12)
+    Nt1,
+    /// SMB 2.0.2
+    Smb2_02,
+    /// SMB 2.1
+    Smb2_10,
+    /// SMB 3.0
+    Smb3_00,
+    /// SMB 3.0.2
+    Smb3_02,
+    /// SMB 3.1.1
+    Smb3_11,
+}
+
+impl SmbProtocol {
+    /// Name of the dialect as used in the Samba configuration
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Nt1 => "NT1",
+            Self::Smb2_02 => "SMB2_02",
+            Self::Smb2_10 => "SMB2_10",
+            Self::Smb3_00 => "SMB3_00",
+            Self::Smb3_02 => "SMB3_02",
+            Self::Smb3_11 => "SMB3_11",
+        }
+    }
+}
+
+impl fmt::Display for SmbProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Share mode option
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum SmbShareMode {
     DenyDos,
     DenyAll,
@@ -119,8 +717,25 @@ impl From<SmbShareMode> for smbc_share_mode {
     }
 }
 
+impl TryFrom<smbc_share_mode> for SmbShareMode {
+    type Error = SmbError;
+
+    fn try_from(mode: smbc_share_mode) -> Result<Self, Self::Error> {
+        match mode {
+            0 => Ok(SmbShareMode::DenyDos),
+            1 => Ok(SmbShareMode::DenyAll),
+            2 => Ok(SmbShareMode::DenyWrite),
+            3 => Ok(SmbShareMode::DenyRead),
+            4 => Ok(SmbShareMode::DenyNone),
+            7 => Ok(SmbShareMode::DenyFcb),
+            _ => Err(SmbError::BadValue),
+        }
+    }
+}
+
 /// Encryption level option
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum SmbEncryptionLevel {
     None,
     Request,
@@ -137,9 +752,54 @@ impl From<SmbEncryptionLevel> for smbc_smb_encrypt_level {
     }
 }
 
+impl TryFrom<smbc_smb_encrypt_level> for SmbEncryptionLevel {
+    type Error = SmbError;
+
+    fn try_from(level: smbc_smb_encrypt_level) -> Result<Self, Self::Error> {
+        match level {
+            0 => Ok(SmbEncryptionLevel::None),
+            1 => Ok(SmbEncryptionLevel::Request),
+            2 => Ok(SmbEncryptionLevel::Require),
+            _ => Err(SmbError::BadValue),
+        }
+    }
+}
+
+/// Normalize the smb.conf parameter name `key` as Samba compares them, ignoring case and spaces
+fn normalize_parameter(key: &str) -> String {
+    key.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Signing policy option
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmbSigningPolicy {
+    /// Never sign
+    Off,
+    /// Sign only if the server requires it
+    IfRequired,
+    /// Refuse to connect to servers which don't sign
+    Required,
+}
+
+impl SmbSigningPolicy {
+    /// Value of the `client signing` parameter
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "disabled",
+            Self::IfRequired => "if_required",
+            Self::Required => "required",
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
 
+    use std::net::SocketAddrV6;
+
     use pretty_assertions::assert_eq;
 
     use super::*;
@@ -147,14 +807,27 @@ mod test {
     #[test]
     fn should_initialize_smb_options() {
         let options = SmbOptions::default();
+        assert_eq!(options.address_family, None);
         assert_eq!(options.browser_max_lmb_count, 0);
         assert_eq!(options.case_sensitive, false);
+        assert_eq!(options.config_file, None);
+        assert_eq!(options.connection_idle_timeout, None);
+        assert_eq!(options.debug_level, DEFAULT_DEBUG_LEVEL);
         assert_eq!(options.encryption_level, SmbEncryptionLevel::None);
         assert_eq!(options.fallback_after_kerberos, false);
         assert_eq!(options.full_time_names, false);
+        assert_eq!(options.max_cached_connections, None);
+        assert_eq!(options.max_protocol, None);
+        assert_eq!(options.min_protocol, None);
+        assert!(options.name_resolve_order.is_empty());
         assert_eq!(options.no_auto_anonymous_login, false);
         assert_eq!(options.one_share_per_server, false);
         assert_eq!(options.open_share_mode, SmbShareMode::DenyNone);
+        assert!(options.parameters.is_empty());
+        assert_eq!(options.port, None);
+        assert_eq!(options.posix_extensions, false);
+        assert_eq!(options.signing, None);
+        assert_eq!(options.transport, None);
         assert_eq!(options.url_encode_readdir_entries, false);
         assert_eq!(options.use_ccache, false);
         assert_eq!(options.use_kerberos, false);
@@ -163,25 +836,63 @@ mod test {
     #[test]
     fn should_configure_smb_options() {
         let options = SmbOptions::default()
+            .address_family(SmbAddressFamily::Ipv6)
             .browser_max_lmb_count(10)
             .case_sensitive(true)
+            .config_file("/etc/samba/smb.conf")
+            .connection_idle_timeout(Duration::from_secs(300))
+            .debug_level(10)
             .encryption_level(SmbEncryptionLevel::Require)
             .fallback_after_kerberos(true)
             .full_time_names(true)
+            .max_cached_connections(4)
+            .max_protocol(SmbProtocol::Smb3_11)
+            .min_protocol(SmbProtocol::Smb2_02)
+            .name_resolve_order([SmbNameResolver::Host, SmbNameResolver::Bcast])
             .no_auto_anonymous_login(true)
             .one_share_per_server(true)
             .open_share_mode(SmbShareMode::DenyAll)
+            .parameter("name resolve order", "host bcast")
+            .port(3445)
+            .posix_extensions(true)
+            .signing(SmbSigningPolicy::Required)
+            .transport(SmbTransport::Direct)
             .url_encode_readdir_entries(true)
             .use_ccache(true)
             .use_kerberos(true);
+        assert_eq!(options.address_family, Some(SmbAddressFamily::Ipv6));
         assert_eq!(options.browser_max_lmb_count, 10);
         assert_eq!(options.case_sensitive, true);
+        assert_eq!(
+            options.config_file.as_deref(),
+            Some(Path::new("/etc/samba/smb.conf"))
+        );
+        assert_eq!(
+            options.connection_idle_timeout,
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(options.debug_level, 10);
         assert_eq!(options.encryption_level, SmbEncryptionLevel::Require);
         assert_eq!(options.fallback_after_kerberos, true);
         assert_eq!(options.full_time_names, true);
+        assert_eq!(options.max_cached_connections, Some(4));
+        assert_eq!(options.max_protocol, Some(SmbProtocol::Smb3_11));
+        assert_eq!(options.min_protocol, Some(SmbProtocol::Smb2_02));
+        assert_eq!(
+            options.name_resolve_order,
+            vec![SmbNameResolver::Host, SmbNameResolver::Bcast]
+        );
         assert_eq!(options.no_auto_anonymous_login, true);
         assert_eq!(options.one_share_per_server, true);
         assert_eq!(options.open_share_mode, SmbShareMode::DenyAll);
+        assert_eq!(
+            options.parameters.get("name resolve order").unwrap(),
+            "host bcast"
+        );
+        assert_eq!(options.port, Some(3445));
+        assert_eq!(options.posix_extensions, true);
+        assert_eq!(options.signing, Some(SmbSigningPolicy::Required));
+        assert_eq!(options.transport, Some(SmbTransport::Direct));
         assert_eq!(options.url_encode_readdir_entries, true);
         assert_eq!(options.use_ccache, true);
         assert_eq!(options.use_kerberos, true);
@@ -203,4 +914,293 @@ mod test {
         assert_eq!(smbc_smb_encrypt_level::from(SmbEncryptionLevel::Request), 1);
         assert_eq!(smbc_smb_encrypt_level::from(SmbEncryptionLevel::Require), 2);
     }
+
+    #[test]
+    fn should_convert_i32_to_share_mode_and_encryption_level() {
+        for mode in [
+            SmbShareMode::DenyDos,
+            SmbShareMode::DenyAll,
+            SmbShareMode::DenyWrite,
+            SmbShareMode::DenyRead,
+            SmbShareMode::DenyNone,
+            SmbShareMode::DenyFcb,
+        ] {
+            assert_eq!(
+                SmbShareMode::try_from(smbc_share_mode::from(mode)).unwrap(),
+                mode
+            );
+        }
+        assert!(SmbShareMode::try_from(5).is_err());
+        for level in [
+            SmbEncryptionLevel::None,
+            SmbEncryptionLevel::Request,
+            SmbEncryptionLevel::Require,
+        ] {
+            assert_eq!(
+                SmbEncryptionLevel::try_from(smbc_smb_encrypt_level::from(level)).unwrap(),
+                level
+            );
+        }
+        assert!(SmbEncryptionLevel::try_from(3).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn should_serialize_effective_options() {
+        let options = SmbEffectiveOptions {
+            browser_max_lmb_count: 3,
+            case_sensitive: true,
+            debug_level: 0,
+            encryption_level: SmbEncryptionLevel::Require,
+            fallback_after_kerberos: false,
+            full_time_names: false,
+            netbios_name: "PAVAO".to_string(),
+            no_auto_anonymous_login: false,
+            one_share_per_server: true,
+            open_share_mode: SmbShareMode::DenyNone,
+            port: Some(3445),
+            posix_extensions: false,
+            timeout: Duration::from_secs(20),
+            url_encode_readdir_entries: false,
+            use_ccache: false,
+            use_kerberos: false,
+            user: "test".to_string(),
+            workgroup: "pavao".to_string(),
+        };
+        let json = serde_json::to_value(&options).unwrap();
+        assert_eq!(json["encryption_level"], "Require");
+        assert_eq!(json["open_share_mode"], "DenyNone");
+        assert_eq!(json["port"], 3445);
+        assert_eq!(json["user"], "test");
+    }
+
+    #[test]
+    fn should_convert_signing_policy_to_str() {
+        assert_eq!(SmbSigningPolicy::Off.as_str(), "disabled");
+        assert_eq!(SmbSigningPolicy::IfRequired.as_str(), "if_required");
+        assert_eq!(SmbSigningPolicy::Required.as_str(), "required");
+    }
+
+    #[test]
+    fn should_validate_protocol_range() {
+        assert!(SmbOptions::default().validate().is_ok());
+        assert!(SmbOptions::default()
+            .min_protocol(SmbProtocol::Smb3_11)
+            .max_protocol(SmbProtocol::Smb3_11)
+            .validate()
+            .is_ok());
+        assert!(SmbOptions::default()
+            .min_protocol(SmbProtocol::Smb3_11)
+            .validate()
+            .is_ok());
+        assert_eq!(
+            SmbOptions::default()
+                .min_protocol(SmbProtocol::Smb3_00)
+                .max_protocol(SmbProtocol::Nt1)
+                .validate()
+                .unwrap_err(),
+            SmbError::BadOption("min protocol SMB3_00 is higher than max protocol NT1".to_string())
+        );
+    }
+
+    #[test]
+    fn should_validate_configuration() {
+        assert!(SmbOptions::default()
+            .parameter("client ipc signing", "required")
+            .validate()
+            .is_ok());
+        assert!(SmbOptions::default()
+            .parameter("", "required")
+            .validate()
+            .is_err());
+        assert!(SmbOptions::default()
+            .parameter("a = b", "c")
+            .validate()
+            .is_err());
+        assert!(SmbOptions::default()
+            .parameter("socket options", "TCP_NODELAY\n[share]")
+            .validate()
+            .is_err());
+        assert!(SmbOptions::default()
+            .config_file("/this/file/does/not/exist.conf")
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn should_reject_session_security_parameters() {
+        assert!(matches!(
+            SmbOptions::default()
+                .parameter("Client  Signing", "required")
+                .validate(),
+            Err(SmbError::BadOption(_))
+        ));
+        assert!(SmbOptions::default()
+            .parameter("client smb encrypt", "required")
+            .validate()
+            .is_err());
+        assert!(SmbOptions::default()
+            .parameter("client protection", "encrypt")
+            .validate()
+            .is_err());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("smb.conf");
+        std::fs::write(
+            &path,
+            "[global]\n\t# client signing = required\n\tworkgroup = pavao\n[share]\n\tclient signing = required\n",
+        )
+        .unwrap();
+        assert!(SmbOptions::default().config_file(&path).validate().is_ok());
+        std::fs::write(&path, "[global]\n\tclient smb encrypt = required\n").unwrap();
+        assert!(matches!(
+            SmbOptions::default().config_file(&path).validate(),
+            Err(SmbError::BadOption(_))
+        ));
+    }
+
+    #[test]
+    fn should_render_configuration() {
+        assert_eq!(SmbOptions::default().configuration(), None);
+        assert_eq!(
+            SmbOptions::default()
+                .config_file("/etc/pavao.conf")
+                .configuration(),
+            None
+        );
+        assert_eq!(
+            SmbOptions::default()
+                .config_file("/etc/pavao.conf")
+                .signing(SmbSigningPolicy::Required)
+                .parameter("name resolve order", "host")
+                .parameter(" client ipc signing ", "required")
+                .configuration()
+                .unwrap(),
+            "[global]\n\tclient signing = required\n\tclient ipc signing = required\n\tname resolve order = host\n"
+        );
+    }
+
+    #[test]
+    fn should_validate_network_options() {
+        assert!(SmbOptions::default().port(3445).validate().is_ok());
+        assert!(SmbOptions::default().port(0).validate().is_err());
+        assert!(SmbOptions::default()
+            .port(139)
+            .transport(SmbTransport::NetBios)
+            .validate()
+            .is_ok());
+        assert!(SmbOptions::default()
+            .port(445)
+            .transport(SmbTransport::NetBios)
+            .validate()
+            .is_err());
+        assert!(SmbOptions::default()
+            .port(139)
+            .transport(SmbTransport::Direct)
+            .validate()
+            .is_err());
+        assert!(SmbOptions::default()
+            .name_resolve_order([SmbNameResolver::Host, SmbNameResolver::Wins])
+            .validate()
+            .is_ok());
+        assert!(SmbOptions::default()
+            .name_resolve_order([SmbNameResolver::Host, SmbNameResolver::Host])
+            .validate()
+            .is_err());
+        assert!(SmbOptions::default()
+            .max_cached_connections(0)
+            .validate()
+            .is_err());
+        assert!(SmbOptions::default()
+            .address_family(SmbAddressFamily::Ipv4)
+            .use_kerberos(true)
+            .validate()
+            .is_err());
+        assert!(SmbOptions::default()
+            .address_family(SmbAddressFamily::Ipv6)
+            .transport(SmbTransport::NetBios)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn should_render_network_configuration() {
+        assert_eq!(
+            SmbOptions::default()
+                .transport(SmbTransport::NetBios)
+                .name_resolve_order([SmbNameResolver::Wins, SmbNameResolver::Bcast])
+                .configuration()
+                .unwrap(),
+            "[global]\n\tsmb ports = 139\n\tname resolve order = wins bcast\n"
+        );
+    }
+
+    #[test]
+    fn should_check_server_url_port() {
+        let options = SmbOptions::default().port(3445);
+        assert_eq!(
+            options.server_url("smb://localhost:3445").unwrap(),
+            "smb://localhost:3445"
+        );
+        assert_eq!(
+            options.server_url("smb://localhost").unwrap(),
+            "smb://localhost"
+        );
+        assert!(options.server_url("smb://localhost:445").is_err());
+        assert!(options.server_url("smb://localhost:99999").is_err());
+        let netbios = SmbOptions::default().transport(SmbTransport::NetBios);
+        assert!(netbios.server_url("smb://localhost:139").is_ok());
+        assert!(netbios.server_url("smb://localhost:445").is_err());
+        assert!(SmbOptions::default()
+            .transport(SmbTransport::Direct)
+            .server_url("smb://localhost:139")
+            .is_err());
+    }
+
+    #[test]
+    fn should_use_address_of_preferred_family() {
+        let resolve = |host: &str, port: u16| {
+            assert_eq!(host, "nas.local");
+            Ok(vec![
+                SocketAddr::new("192.168.1.10".parse().unwrap(), port),
+                SocketAddr::V6(SocketAddrV6::new("fe80::1".parse().unwrap(), port, 0, 2)),
+            ])
+        };
+        let ipv4 = SmbOptions::default().address_family(SmbAddressFamily::Ipv4);
+        let ipv6 = SmbOptions::default().address_family(SmbAddressFamily::Ipv6);
+        assert_eq!(
+            ipv4.server_url_with("smb://nas.local:3445", resolve)
+                .unwrap(),
+            "smb://192.168.1.10:3445"
+        );
+        assert_eq!(
+            ipv6.server_url_with("smb://user@nas.local/", resolve)
+                .unwrap(),
+            "smb://user@fe80--1s2.ipv6-literal.net/"
+        );
+        // addresses are kept
+        assert_eq!(
+            ipv6.server_url_with("smb://10.0.0.1", |_, _| unreachable!())
+                .unwrap(),
+            "smb://10.0.0.1"
+        );
+        // unresolved names are left to libsmbclient
+        assert_eq!(
+            ipv4.server_url_with("smb://nas.local", |_, _| Err(io::Error::from(
+                io::ErrorKind::NotFound
+            )))
+            .unwrap(),
+            "smb://nas.local"
+        );
+    }
+
+    #[test]
+    fn should_convert_protocol_to_str() {
+        assert_eq!(SmbProtocol::Nt1.to_string(), "NT1");
+        assert_eq!(SmbProtocol::Smb2_02.to_string(), "SMB2_02");
+        assert_eq!(SmbProtocol::Smb2_10.to_string(), "SMB2_10");
+        assert_eq!(SmbProtocol::Smb3_00.to_string(), "SMB3_00");
+        assert_eq!(SmbProtocol::Smb3_02.to_string(), "SMB3_02");
+        assert_eq!(SmbProtocol::Smb3_11.to_string(), "SMB3_11");
+        assert!(SmbProtocol::Nt1 < SmbProtocol::Smb3_11);
+    }
 }
@@ -0,0 +1,122 @@
+//! # Print
+//!
+//! module which exposes the print job types
+
+use std::ffi::CStr;
+use std::io::{self, Write};
+use std::time::SystemTime;
+
+use libc::c_char;
+use pavao_sys::print_job_info;
+
+use super::stat::time_t_to_system_time;
+use super::SmbFile;
+use crate::SmbResult;
+
+/// A print job opened on a print queue. Data written to the job is sent to the printer.
+///
+/// The job is submitted by [`SmbPrintJob::finish`], or when it is dropped ignoring errors.
+pub struct SmbPrintJob<'a> {
+    file: SmbFile<'a>,
+}
+
+impl<'a> SmbPrintJob<'a> {
+    pub(crate) fn new(file: SmbFile<'a>) -> Self {
+        Self { file }
+    }
+
+    /// Submit the job to the printer, returning the error reported by the server
+    pub fn finish(self) -> SmbResult<()> {
+        trace!("submitting print job");
+        self.file.close()
+    }
+}
+
+impl Write for SmbPrintJob<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Information about a job in a print queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmbPrintJobInfo {
+    /// Numeric ID of the print job
+    pub id: u16,
+    /// Print job priority (lower numbers mean higher priority)
+    pub priority: u16,
+    /// Size of the print job
+    pub size: u64,
+    /// Name of the user that owns the print job
+    pub user: String,
+    /// Name of the print job. It is empty for anonymous print jobs
+    pub name: String,
+    /// Time the print job was spooled
+    pub spooled: SystemTime,
+}
+
+impl From<&print_job_info> for SmbPrintJobInfo {
+    fn from(info: &print_job_info) -> Self {
+        Self {
+            id: info.id,
+            priority: info.priority,
+            size: info.size as u64,
+            user: char_array_to_string(&info.user),
+            name: char_array_to_string(&info.name),
+            spooled: time_t_to_system_time(info.t),
+        }
+    }
+}
+
+/// Convert a fixed size char array, which may not be nul-terminated, to a string
+fn char_array_to_string(buf: &[c_char]) -> String {
+    let bytes: Vec<u8> = buf.iter().map(|x| *x as u8).collect();
+    match CStr::from_bytes_until_nul(&bytes) {
+        Ok(s) => s.to_string_lossy().to_string(),
+        Err(_) => String::from_utf8_lossy(&bytes).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_convert_print_job_info() {
+        let mut info = print_job_info {
+            id: 12,
+            priority: 1,
+            size: 2048,
+            t: 1000,
+            ..Default::default()
+        };
+        for (i, c) in "test".bytes().enumerate() {
+            info.user[i] = c as c_char;
+        }
+        for (i, c) in "document.pdf".bytes().enumerate() {
+            info.name[i] = c as c_char;
+        }
+        let job = SmbPrintJobInfo::from(&info);
+        assert_eq!(job.id, 12);
+        assert_eq!(job.priority, 1);
+        assert_eq!(job.size, 2048);
+        assert_eq!(job.user.as_str(), "test");
+        assert_eq!(job.name.as_str(), "document.pdf");
+        assert_eq!(job.spooled, UNIX_EPOCH + Duration::from_secs(1000));
+    }
+
+    #[test]
+    fn should_convert_unterminated_char_array() {
+        let buf = ['a' as c_char; 4];
+        assert_eq!(char_array_to_string(&buf).as_str(), "aaaa");
+    }
+}
@@ -0,0 +1,65 @@
+//! # Rename
+//!
+//! options and outcome of `move_path`
+
+/// Options for `move_path`
+#[derive(Debug, Clone, Copy)]
+pub struct SmbMoveOptions {
+    pub(crate) overwrite: bool,
+    pub(crate) copy_fallback: bool,
+}
+
+impl Default for SmbMoveOptions {
+    fn default() -> Self {
+        Self {
+            overwrite: false,
+            copy_fallback: true,
+        }
+    }
+}
+
+impl SmbMoveOptions {
+    /// Replace the destination if it exists (default: `false`).
+    ///
+    /// When `false`, the move fails if the destination exists, whatever the server does on rename.
+    /// When `true`, the destination is moved aside, then removed once the move succeeds or restored if it fails;
+    /// a directory can only replace an empty directory
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Copy then delete the source when it can't be renamed,
+    /// e.g. because the destination is on another share or server (default: `true`)
+    pub fn copy_fallback(mut self, copy_fallback: bool) -> Self {
+        self.copy_fallback = copy_fallback;
+        self
+    }
+}
+
+/// Strategy used by `move_path` to move an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmbMoveStrategy {
+    /// The entry has been renamed by the server
+    Rename,
+    /// The entry has been copied to the destination, preserving its metadata, then removed
+    CopyDelete,
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_configure_move_options() {
+        let options = SmbMoveOptions::default();
+        assert_eq!(options.overwrite, false);
+        assert_eq!(options.copy_fallback, true);
+        let options = options.overwrite(true).copy_fallback(false);
+        assert_eq!(options.overwrite, true);
+        assert_eq!(options.copy_fallback, false);
+    }
+}
@@ -0,0 +1,490 @@
+//! # Security
+//!
+//! module which exposes the NT security descriptor and its access control entries,
+//! as represented by libsmbclient in the `system.nt_sec_desc.*` extended attributes
+
+use std::fmt;
+use std::str::FromStr;
+
+use bitflags::bitflags;
+
+use crate::SmbError;
+
+/// NT security descriptor of a file, made up of an owner, a group and a discretionary ACL.
+///
+/// It can be parsed from and serialized to the string format used by libsmbclient, e.g.
+/// `REVISION:1,OWNER:S-1-5-32-544,GROUP:S-1-5-32-545,ACL:S-1-1-0:0/3/0x001f01ff`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmbSecurityDescriptor {
+    /// Security descriptor revision
+    pub revision: u32,
+    /// Owner of the file. If `None` the owner is left unchanged when the descriptor is set
+    pub owner: Option<SmbTrustee>,
+    /// Primary group of the file. If `None` the group is left unchanged when the descriptor is set
+    pub group: Option<SmbTrustee>,
+    /// Access control entries of the discretionary ACL
+    pub acl: Vec<SmbAce>,
+}
+
+impl Default for SmbSecurityDescriptor {
+    fn default() -> Self {
+        Self {
+            revision: 1,
+            owner: None,
+            group: None,
+            acl: Vec::new(),
+        }
+    }
+}
+
+impl SmbSecurityDescriptor {
+    /// Returns whether any trustee in the descriptor is expressed as a name rather than a SID
+    pub fn has_names(&self) -> bool {
+        self.owner
+            .as_ref()
+            .map(SmbTrustee::is_name)
+            .unwrap_or(false)
+            || self
+                .group
+                .as_ref()
+                .map(SmbTrustee::is_name)
+                .unwrap_or(false)
+            || self.acl.iter().any(|ace| ace.trustee.is_name())
+    }
+
+    /// Add `ace` to the ACL.
+    ///
+    /// Denied entries are placed after the existing denied entries and before any allowed entry,
+    /// as required by the canonical ACL order; any other entry is appended.
+    pub fn add_ace(&mut self, ace: SmbAce) {
+        if ace.ace_type == SmbAceType::Denied {
+            let pos = self
+                .acl
+                .iter()
+                .position(|x| x.ace_type != SmbAceType::Denied)
+                .unwrap_or(self.acl.len());
+            self.acl.insert(pos, ace);
+        } else {
+            self.acl.push(ace);
+        }
+    }
+
+    /// Remove all the entries equal to `ace` from the ACL.
+    ///
+    /// Returns whether any entry has been removed
+    pub fn remove_ace(&mut self, ace: &SmbAce) -> bool {
+        let len = self.acl.len();
+        self.acl.retain(|x| x != ace);
+        self.acl.len() != len
+    }
+}
+
+impl fmt::Display for SmbSecurityDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "REVISION:{}", self.revision)?;
+        if let Some(owner) = &self.owner {
+            write!(f, ",OWNER:{owner}")?;
+        }
+        if let Some(group) = &self.group {
+            write!(f, ",GROUP:{group}")?;
+        }
+        for ace in self.acl.iter() {
+            write!(f, ",ACL:{ace}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SmbSecurityDescriptor {
+    type Err = SmbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut sd = Self::default();
+        for token in s.trim_end_matches('\0').split(',') {
+            let token = token.trim();
+            if token.is_empty() {
+                continue;
+            }
+            let (key, value) = token.split_once(':').ok_or(SmbError::BadValue)?;
+            match key.trim().to_ascii_uppercase().as_str() {
+                "REVISION" => sd.revision = parse_number(value)?,
+                "OWNER" => sd.owner = Some(SmbTrustee::from_str(value)?),
+                "GROUP" => sd.group = Some(SmbTrustee::from_str(value)?),
+                "ACL" => sd.acl.push(SmbAce::from_str(value)?),
+                _ => return Err(SmbError::BadValue),
+            }
+        }
+        Ok(sd)
+    }
+}
+
+/// Access control entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmbAce {
+    /// Trustee the entry applies to
+    pub trustee: SmbTrustee,
+    /// Entry type
+    pub ace_type: SmbAceType,
+    /// Inheritance and audit flags
+    pub flags: SmbAceFlags,
+    /// Rights allowed or denied by the entry
+    pub access_mask: SmbAccessMask,
+}
+
+impl SmbAce {
+    /// Construct a new `SmbAce`
+    pub fn new(
+        trustee: SmbTrustee,
+        ace_type: SmbAceType,
+        flags: SmbAceFlags,
+        access_mask: SmbAccessMask,
+    ) -> Self {
+        Self {
+            trustee,
+            ace_type,
+            flags,
+            access_mask,
+        }
+    }
+}
+
+impl fmt::Display for SmbAce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}/{}/0x{:08x}",
+            self.trustee,
+            u8::from(self.ace_type),
+            self.flags.bits(),
+            self.access_mask.bits()
+        )
+    }
+}
+
+impl FromStr for SmbAce {
+    type Err = SmbError;
+
+    /// Parse an ACE in the form `trustee:type/flags/mask`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (trustee, rights) = s.trim().rsplit_once(':').ok_or(SmbError::BadValue)?;
+        let mut rights = rights.split('/');
+        let (Some(ace_type), Some(flags), Some(mask), None) =
+            (rights.next(), rights.next(), rights.next(), rights.next())
+        else {
+            return Err(SmbError::BadValue);
+        };
+        let ace_type = match ace_type.trim().to_ascii_uppercase().as_str() {
+            "ALLOWED" => SmbAceType::Allowed,
+            "DENIED" => SmbAceType::Denied,
+            other => SmbAceType::from(parse_byte(other)?),
+        };
+        let access_mask = match mask.trim().to_ascii_uppercase().as_str() {
+            "FULL" => SmbAccessMask::FULL,
+            "CHANGE" => SmbAccessMask::CHANGE,
+            "READ" => SmbAccessMask::READ,
+            other => SmbAccessMask::from_bits_retain(parse_number(other)?),
+        };
+        Ok(Self {
+            trustee: SmbTrustee::from_str(trustee)?,
+            ace_type,
+            flags: SmbAceFlags::from_bits_retain(parse_byte(flags)?),
+            access_mask,
+        })
+    }
+}
+
+/// Type of an access control entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SmbAceType {
+    /// `ACCESS_ALLOWED_ACE_TYPE`
+    Allowed,
+    /// `ACCESS_DENIED_ACE_TYPE`
+    Denied,
+    /// `SYSTEM_AUDIT_ACE_TYPE`
+    Audit,
+    /// `SYSTEM_ALARM_ACE_TYPE`
+    Alarm,
+    /// Any other ACE type
+    Other(u8),
+}
+
+impl From<u8> for SmbAceType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Allowed,
+            1 => Self::Denied,
+            2 => Self::Audit,
+            3 => Self::Alarm,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl From<SmbAceType> for u8 {
+    fn from(value: SmbAceType) -> Self {
+        match value {
+            SmbAceType::Allowed => 0,
+            SmbAceType::Denied => 1,
+            SmbAceType::Audit => 2,
+            SmbAceType::Alarm => 3,
+            SmbAceType::Other(other) => other,
+        }
+    }
+}
+
+bitflags! {
+    /// Access control entry flags
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct SmbAceFlags: u8 {
+        const OBJECT_INHERIT = 0x01;
+        const CONTAINER_INHERIT = 0x02;
+        const NO_PROPAGATE_INHERIT = 0x04;
+        const INHERIT_ONLY = 0x08;
+        const INHERITED = 0x10;
+        const SUCCESSFUL_ACCESS = 0x40;
+        const FAILED_ACCESS = 0x80;
+    }
+}
+
+bitflags! {
+    /// Access rights of an access control entry
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct SmbAccessMask: u32 {
+        const READ_DATA = 0x0000_0001;
+        const WRITE_DATA = 0x0000_0002;
+        const APPEND_DATA = 0x0000_0004;
+        const READ_EA = 0x0000_0008;
+        const WRITE_EA = 0x0000_0010;
+        const EXECUTE = 0x0000_0020;
+        const DELETE_CHILD = 0x0000_0040;
+        const READ_ATTRIBUTES = 0x0000_0080;
+        const WRITE_ATTRIBUTES = 0x0000_0100;
+        const DELETE = 0x0001_0000;
+        const READ_CONTROL = 0x0002_0000;
+        const WRITE_DAC = 0x0004_0000;
+        const WRITE_OWNER = 0x0008_0000;
+        const SYNCHRONIZE = 0x0010_0000;
+        const ACCESS_SYSTEM_SECURITY = 0x0100_0000;
+        const MAXIMUM_ALLOWED = 0x0200_0000;
+        const GENERIC_ALL = 0x1000_0000;
+        const GENERIC_EXECUTE = 0x2000_0000;
+        const GENERIC_WRITE = 0x4000_0000;
+        const GENERIC_READ = 0x8000_0000;
+        /// Read permission, as `READ` in smbcacls
+        const READ = 0x0012_00a9;
+        /// Change permission, as `CHANGE` in smbcacls
+        const CHANGE = 0x0013_01bf;
+        /// Full control, as `FULL` in smbcacls
+        const FULL = 0x001f_01ff;
+    }
+}
+
+/// Trustee of an access control entry, owner or group of a security descriptor
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SmbTrustee {
+    /// Security identifier, e.g. `S-1-5-32-544`
+    Sid(String),
+    /// Resolved account name, e.g. `BUILTIN\Administrators`
+    Name(String),
+}
+
+impl SmbTrustee {
+    /// Returns whether the trustee is expressed as a resolved name
+    pub fn is_name(&self) -> bool {
+        matches!(self, Self::Name(_))
+    }
+
+    /// Returns whether `s` is a valid SID string
+    fn is_sid(s: &str) -> bool {
+        let mut parts = s.split('-');
+        matches!(parts.next(), Some("S") | Some("s")) && parts.next() == Some("1") && {
+            let parts: Vec<&str> = parts.collect();
+            !parts.is_empty()
+                && parts
+                    .iter()
+                    .all(|x| !x.is_empty() && x.bytes().all(|b| b.is_ascii_digit()))
+        }
+    }
+}
+
+impl fmt::Display for SmbTrustee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sid(sid) => write!(f, "{sid}"),
+            Self::Name(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl FromStr for SmbTrustee {
+    type Err = SmbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            Err(SmbError::BadValue)
+        } else if Self::is_sid(s) {
+            Ok(Self::Sid(s.to_string()))
+        } else {
+            Ok(Self::Name(s.to_string()))
+        }
+    }
+}
+
+/// Parse a decimal or `0x` prefixed hexadecimal number
+fn parse_number(s: &str) -> Result<u32, SmbError> {
+    let s = s.trim();
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => s.parse::<u32>(),
+    }
+    .map_err(|_| SmbError::BadValue)
+}
+
+/// Parse a decimal or `0x` prefixed hexadecimal number which must fit a byte
+fn parse_byte(s: &str) -> Result<u8, SmbError> {
+    u8::try_from(parse_number(s)?).map_err(|_| SmbError::BadValue)
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_security_descriptor() {
+        let sd = SmbSecurityDescriptor::from_str(
+            "REVISION:1,OWNER:S-1-5-21-1-2-3-1000,GROUP:BUILTIN\\Users,ACL:S-1-1-0:0/3/0x001f01ff,ACL:Everyone:1/0/0x00000001",
+        )
+        .unwrap();
+        assert_eq!(sd.revision, 1);
+        assert_eq!(
+            sd.owner,
+            Some(SmbTrustee::Sid("S-1-5-21-1-2-3-1000".to_string()))
+        );
+        assert_eq!(
+            sd.group,
+            Some(SmbTrustee::Name("BUILTIN\\Users".to_string()))
+        );
+        assert_eq!(sd.acl.len(), 2);
+        assert_eq!(
+            sd.acl[0],
+            SmbAce::new(
+                SmbTrustee::Sid("S-1-1-0".to_string()),
+                SmbAceType::Allowed,
+                SmbAceFlags::OBJECT_INHERIT | SmbAceFlags::CONTAINER_INHERIT,
+                SmbAccessMask::FULL
+            )
+        );
+        assert_eq!(sd.acl[1].ace_type, SmbAceType::Denied);
+        assert_eq!(sd.acl[1].access_mask, SmbAccessMask::READ_DATA);
+        assert!(sd.has_names());
+    }
+
+    #[test]
+    fn should_parse_ace_with_symbolic_values() {
+        let ace = SmbAce::from_str("S-1-1-0:ALLOWED/0/FULL").unwrap();
+        assert_eq!(ace.ace_type, SmbAceType::Allowed);
+        assert_eq!(ace.access_mask, SmbAccessMask::FULL);
+        let ace = SmbAce::from_str("S-1-1-0:DENIED/0x10/READ").unwrap();
+        assert_eq!(ace.ace_type, SmbAceType::Denied);
+        assert_eq!(ace.flags, SmbAceFlags::INHERITED);
+        assert_eq!(ace.access_mask, SmbAccessMask::READ);
+    }
+
+    #[test]
+    fn should_fail_parsing_bad_security_descriptor() {
+        assert!(SmbSecurityDescriptor::from_str("FOO:1").is_err());
+        assert!(SmbSecurityDescriptor::from_str("REVISION:abc").is_err());
+        assert!(SmbSecurityDescriptor::from_str("ACL:S-1-1-0:0/0").is_err());
+        assert!(SmbSecurityDescriptor::from_str("ACL:S-1-1-0:0/256/0x1").is_err());
+        assert!(SmbSecurityDescriptor::from_str("OWNER:").is_err());
+    }
+
+    #[test]
+    fn should_serialize_security_descriptor() {
+        let mut sd = SmbSecurityDescriptor {
+            owner: Some(SmbTrustee::Sid("S-1-5-32-544".to_string())),
+            ..Default::default()
+        };
+        sd.add_ace(SmbAce::new(
+            SmbTrustee::Sid("S-1-1-0".to_string()),
+            SmbAceType::Allowed,
+            SmbAceFlags::empty(),
+            SmbAccessMask::READ,
+        ));
+        assert_eq!(
+            sd.to_string(),
+            "REVISION:1,OWNER:S-1-5-32-544,ACL:S-1-1-0:0/0/0x001200a9"
+        );
+    }
+
+    #[test]
+    fn should_keep_denied_aces_first() {
+        let allowed = SmbAce::new(
+            SmbTrustee::Sid("S-1-1-0".to_string()),
+            SmbAceType::Allowed,
+            SmbAceFlags::empty(),
+            SmbAccessMask::READ,
+        );
+        let denied = SmbAce::new(
+            SmbTrustee::Sid("S-1-5-32-546".to_string()),
+            SmbAceType::Denied,
+            SmbAceFlags::empty(),
+            SmbAccessMask::WRITE_DATA,
+        );
+        let mut sd = SmbSecurityDescriptor::default();
+        sd.add_ace(allowed.clone());
+        sd.add_ace(denied.clone());
+        assert_eq!(sd.acl, vec![denied.clone(), allowed.clone()]);
+        assert!(sd.remove_ace(&denied));
+        assert!(!sd.remove_ace(&denied));
+        assert_eq!(sd.acl, vec![allowed]);
+    }
+
+    fn trustee_strategy() -> impl Strategy<Value = SmbTrustee> {
+        prop_oneof![
+            proptest::collection::vec(any::<u32>(), 1..6).prop_map(|parts| {
+                let parts: Vec<String> = parts.iter().map(|x| x.to_string()).collect();
+                SmbTrustee::Sid(format!("S-1-{}", parts.join("-")))
+            }),
+            "[a-z][A-Za-z0-9_.]{0,15}(\\\\[A-Za-z][A-Za-z0-9_. -]{0,14}[A-Za-z0-9])?"
+                .prop_map(SmbTrustee::Name),
+        ]
+    }
+
+    fn ace_strategy() -> impl Strategy<Value = SmbAce> {
+        (trustee_strategy(), any::<u8>(), any::<u8>(), any::<u32>()).prop_map(
+            |(trustee, ace_type, flags, mask)| {
+                SmbAce::new(
+                    trustee,
+                    SmbAceType::from(ace_type),
+                    SmbAceFlags::from_bits_retain(flags),
+                    SmbAccessMask::from_bits_retain(mask),
+                )
+            },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn should_round_trip_security_descriptor(
+            revision in any::<u32>(),
+            owner in proptest::option::of(trustee_strategy()),
+            group in proptest::option::of(trustee_strategy()),
+            acl in proptest::collection::vec(ace_strategy(), 0..8),
+        ) {
+            let sd = SmbSecurityDescriptor { revision, owner, group, acl };
+            let parsed = SmbSecurityDescriptor::from_str(&sd.to_string()).unwrap();
+            prop_assert_eq!(parsed, sd);
+        }
+
+        #[test]
+        fn should_round_trip_ace(ace in ace_strategy()) {
+            prop_assert_eq!(SmbAce::from_str(&ace.to_string()).unwrap(), ace);
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! # Session
+//!
+//! security properties of the client sessions
+
+/// Security required on the sessions of the client by its configuration.
+///
+/// libsmbclient doesn't report what has been negotiated with the server, so this is not
+/// a verification of the sessions: a property is reported only when the configuration
+/// makes the client refuse to connect without it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmbSessionSecurity {
+    /// Whether the sessions are guaranteed to be signed (or encrypted, which implies integrity)
+    pub signed: bool,
+    /// Whether the sessions are guaranteed to be encrypted
+    pub encrypted: bool,
+}
@@ -0,0 +1,184 @@
+//! # Snapshot
+//!
+//! previous versions of files, exposed by the server as `@GMT-YYYY.MM.DD-HH.MM.SS` tokens
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::SmbError;
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// Snapshot (shadow copy) of a share, identified by its UTC creation time.
+///
+/// Snapshots are formatted and parsed as the `@GMT-YYYY.MM.DD-HH.MM.SS` token understood by the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SmbSnapshot {
+    /// Seconds since the unix epoch
+    secs: i64,
+}
+
+impl SmbSnapshot {
+    /// Get the time at which the snapshot has been taken
+    pub fn time(&self) -> SystemTime {
+        if self.secs >= 0 {
+            UNIX_EPOCH + Duration::from_secs(self.secs as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(self.secs.unsigned_abs())
+        }
+    }
+
+    /// Get `path` as of this snapshot
+    pub(crate) fn path(&self, path: &str) -> String {
+        format!("/{}/{}", self, path.trim_start_matches('/'))
+    }
+}
+
+impl From<SystemTime> for SmbSnapshot {
+    /// Build a snapshot from `time`, truncated to seconds
+    fn from(time: SystemTime) -> Self {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs_f64().ceil() as i64),
+        };
+        Self { secs }
+    }
+}
+
+impl fmt::Display for SmbSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let days = self.secs.div_euclid(SECS_PER_DAY);
+        let secs = self.secs.rem_euclid(SECS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        write!(
+            f,
+            "@GMT-{:04}.{:02}.{:02}-{:02}.{:02}.{:02}",
+            year,
+            month,
+            day,
+            secs / 3600,
+            (secs % 3600) / 60,
+            secs % 60
+        )
+    }
+}
+
+impl FromStr for SmbSnapshot {
+    type Err = SmbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("@GMT-").ok_or(SmbError::BadValue)?;
+        let (date, time) = s.split_once('-').ok_or(SmbError::BadValue)?;
+        let date = parse_fields(date, 4)?;
+        let time = parse_fields(time, 2)?;
+        let (month, day) = (date[1], date[2]);
+        if !(1..=12).contains(&month)
+            || !(1..=31).contains(&day)
+            || time[0] > 23
+            || time[1] > 59
+            || time[2] > 59
+        {
+            return Err(SmbError::BadValue);
+        }
+        let days = days_from_civil(date[0], month as u32, day as u32);
+        Ok(Self {
+            secs: days * SECS_PER_DAY + time[0] * 3600 + time[1] * 60 + time[2],
+        })
+    }
+}
+
+/// Parse three dot-separated numbers; the first one has `first_len` digits, the others 2
+fn parse_fields(s: &str, first_len: usize) -> Result<[i64; 3], SmbError> {
+    let mut fields = [0; 3];
+    let mut parts = s.split('.');
+    for (i, field) in fields.iter_mut().enumerate() {
+        let part = parts.next().ok_or(SmbError::BadValue)?;
+        let len = if i == 0 { first_len } else { 2 };
+        if part.len() != len || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(SmbError::BadValue);
+        }
+        *field = part.parse().map_err(|_| SmbError::BadValue)?;
+    }
+    match parts.next() {
+        Some(_) => Err(SmbError::BadValue),
+        None => Ok(fields),
+    }
+}
+
+/// Days since the unix epoch of a date in the proleptic gregorian calendar
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Date in the proleptic gregorian calendar of the days since the unix epoch
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_snapshot() {
+        let snapshot = SmbSnapshot::from_str("@GMT-2024.02.29-13.04.05").unwrap();
+        assert_eq!(
+            snapshot.time(),
+            UNIX_EPOCH + Duration::from_secs(1_709_211_845)
+        );
+        assert_eq!(snapshot.to_string(), "@GMT-2024.02.29-13.04.05");
+        assert_eq!(
+            SmbSnapshot::from(UNIX_EPOCH).to_string(),
+            "@GMT-1970.01.01-00.00.00"
+        );
+    }
+
+    #[test]
+    fn should_not_parse_bad_snapshots() {
+        assert!(SmbSnapshot::from_str("GMT-2024.02.29-13.04.05").is_err());
+        assert!(SmbSnapshot::from_str("@GMT-2024.02.29").is_err());
+        assert!(SmbSnapshot::from_str("@GMT-2024.13.01-00.00.00").is_err());
+        assert!(SmbSnapshot::from_str("@GMT-2024.01.01-24.00.00").is_err());
+        assert!(SmbSnapshot::from_str("@GMT-2024.1.01-00.00.00").is_err());
+        assert!(SmbSnapshot::from_str("@GMT-2024.01.01-00.00.00.00").is_err());
+        assert!(SmbSnapshot::from_str(".snapshots").is_err());
+    }
+
+    #[test]
+    fn should_get_path_at_snapshot() {
+        let snapshot = SmbSnapshot::from_str("@GMT-2024.02.29-13.04.05").unwrap();
+        assert_eq!(
+            snapshot.path("/dir/file.txt"),
+            "/@GMT-2024.02.29-13.04.05/dir/file.txt"
+        );
+        assert_eq!(snapshot.path("/"), "/@GMT-2024.02.29-13.04.05/");
+    }
+
+    proptest! {
+        #[test]
+        fn should_format_and_parse_snapshots(secs in 0i64..253_402_300_800) {
+            let snapshot = SmbSnapshot { secs };
+            prop_assert_eq!(SmbSnapshot::from_str(&snapshot.to_string()).unwrap(), snapshot);
+        }
+    }
+}
@@ -5,16 +5,13 @@
 
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use libc::{stat, statvfs, time_t};
+use libc::{stat, statvfs, time_t, timespec};
 use pavao_sys::libsmb_file_info;
 
-use super::SmbMode;
+use super::{SmbFileAttributes, SmbMode};
 use crate::utils::char_ptr_to_string;
 use crate::{SmbDirentType, SmbError};
 
-/// DOS Attribute mask for DIRECTORY
-const FILE_ATTRIBUTE_DIRECTORY: u16 = 0x0010;
-
 /// Smb statvfs type
 #[derive(Debug, Clone)]
 pub struct SmbStatVfs {
@@ -143,11 +140,21 @@ impl From<statvfs> for SmbStatVfs {
 pub struct SmbStat {
     /// Last access time
     pub accessed: SystemTime,
+    /// DOS attributes of file; `None` if they are not reported.
+    ///
+    /// `SmbClient::stat` doesn't report them, since stat doesn't carry the DOS attributes;
+    /// they are reported by `list_dirplus2`, or can be read with `SmbClient::get_attributes`
+    pub attributes: Option<SmbFileAttributes>,
     /// Blocks occupied by file
     pub blocks: i64,
     /// Block size
     pub blksize: i64,
-    /// Creation time
+    /// Change time
+    pub changed: SystemTime,
+    /// Creation (birth) time; if it isn't reported, it is the change time.
+    ///
+    /// `list_dirplus2` reports it with full precision. `SmbClient::stat` only reads it with
+    /// `SmbOptions::full_time_names`, at the cost of an extra round trip, and libsmbclient reports it in whole seconds
     pub created: SystemTime,
     /// Device
     pub dev: i32,
@@ -169,7 +176,11 @@ pub struct SmbStat {
 impl From<stat> for SmbStat {
     fn from(s: stat) -> Self {
         Self {
-            accessed: time_t_to_system_time(s.st_atime),
+            accessed: timespec_to_system_time(timespec {
+                tv_sec: s.st_atime,
+                tv_nsec: s.st_atime_nsec as _,
+            }),
+            attributes: None,
             blocks: s.st_blocks as i64,
             #[cfg(target_os = "android")]
             blksize: s.st_blksize as i64,
@@ -183,7 +194,14 @@ impl From<stat> for SmbStat {
             blksize: s.st_blksize as i64,
             #[cfg(linux_riscv64)]
             blksize: s.st_blksize as i64,
-            created: time_t_to_system_time(s.st_ctime),
+            changed: timespec_to_system_time(timespec {
+                tv_sec: s.st_ctime,
+                tv_nsec: s.st_ctime_nsec as _,
+            }),
+            created: timespec_to_system_time(timespec {
+                tv_sec: s.st_ctime,
+                tv_nsec: s.st_ctime_nsec as _,
+            }),
             #[cfg(target_os = "openbsd")]
             blksize: s.st_blksize as i64,
             #[cfg(target_os = "android")]
@@ -200,7 +218,10 @@ impl From<stat> for SmbStat {
             dev: s.st_dev as i32,
             gid: s.st_gid,
             mode: SmbMode::from(s.st_mode),
-            modified: time_t_to_system_time(s.st_mtime),
+            modified: timespec_to_system_time(timespec {
+                tv_sec: s.st_mtime,
+                tv_nsec: s.st_mtime_nsec as _,
+            }),
             #[cfg(target_os = "openbsd")]
             dev: s.st_dev as i32,
             #[cfg(target_os = "android")]
@@ -263,9 +284,14 @@ pub struct SmbDirentInfo {
 }
 
 impl SmbDirentInfo {
+    /// Get DOS attributes of file
+    pub fn attributes(&self) -> SmbFileAttributes {
+        SmbFileAttributes::from(self.attrs)
+    }
+
     /// Get directory entity type
     pub fn get_type(&self) -> SmbDirentType {
-        if self.attrs & FILE_ATTRIBUTE_DIRECTORY != 0 {
+        if self.attributes().contains(SmbFileAttributes::DIRECTORY) {
             SmbDirentType::Dir
         } else {
             SmbDirentType::File
@@ -283,6 +309,36 @@ impl SmbDirentInfo {
     }
 }
 
+/// SMB directory entity with its full stat
+#[derive(Debug, Clone)]
+pub struct SmbDirentStat {
+    /// Directory entity metadata
+    pub info: SmbDirentInfo,
+    /// Stat of the directory entity
+    pub stat: SmbStat,
+}
+
+impl SmbDirentStat {
+    /// Construct a new `SmbDirentStat`; the DOS attributes and the birth time of `stat` are taken from `info`
+    pub(crate) fn new(info: SmbDirentInfo, mut stat: SmbStat) -> Self {
+        stat.attributes = Some(info.attributes());
+        if info.btime != UNIX_EPOCH {
+            stat.created = info.btime;
+        }
+        Self { info, stat }
+    }
+
+    /// Get directory entity type
+    pub fn get_type(&self) -> SmbDirentType {
+        self.info.get_type()
+    }
+
+    /// Get name
+    pub fn name(&self) -> &str {
+        self.info.name()
+    }
+}
+
 impl TryFrom<libsmb_file_info> for SmbDirentInfo {
     type Error = SmbError;
 
@@ -294,10 +350,10 @@ impl TryFrom<libsmb_file_info> for SmbDirentInfo {
             name,
             short_name,
             size: di.size as u64,
-            ctime: time_t_to_system_time(di.ctime_ts.tv_sec),
-            btime: time_t_to_system_time(di.btime_ts.tv_sec),
-            mtime: time_t_to_system_time(di.mtime_ts.tv_sec),
-            atime: time_t_to_system_time(di.atime_ts.tv_sec),
+            ctime: timespec_to_system_time(di.ctime_ts),
+            btime: timespec_to_system_time(di.btime_ts),
+            mtime: timespec_to_system_time(di.mtime_ts),
+            atime: timespec_to_system_time(di.atime_ts),
             uid: di.uid,
             gid: di.gid,
             attrs: di.attrs,
@@ -305,16 +361,29 @@ impl TryFrom<libsmb_file_info> for SmbDirentInfo {
     }
 }
 
-fn time_t_to_system_time(t: time_t) -> SystemTime {
+pub(crate) fn time_t_to_system_time(t: time_t) -> SystemTime {
     UNIX_EPOCH
         .checked_add(Duration::from_secs(t as u64))
         .unwrap_or(UNIX_EPOCH)
 }
 
+/// Convert a timespec to a system time, keeping the nanoseconds; times before the unix epoch are clamped to it
+pub(crate) fn timespec_to_system_time(ts: timespec) -> SystemTime {
+    if ts.tv_sec < 0 {
+        return UNIX_EPOCH;
+    }
+    UNIX_EPOCH
+        .checked_add(Duration::new(
+            ts.tv_sec as u64,
+            ts.tv_nsec.clamp(0, 999_999_999) as u32,
+        ))
+        .unwrap_or(UNIX_EPOCH)
+}
+
 #[cfg(test)]
 mod test {
 
-    use pretty_assertions::assert_ne;
+    use pretty_assertions::{assert_eq, assert_ne};
 
     use super::*;
 
@@ -322,4 +391,48 @@ mod test {
     fn should_convert_time_t_into_system_time() {
         assert_ne!(time_t_to_system_time(1000), UNIX_EPOCH);
     }
+
+    #[test]
+    fn should_convert_timespec_into_system_time() {
+        assert_eq!(
+            timespec_to_system_time(timespec {
+                tv_sec: 1_700_000_000,
+                tv_nsec: 123_456_789,
+            }),
+            UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789)
+        );
+        assert_eq!(
+            timespec_to_system_time(timespec {
+                tv_sec: -1,
+                tv_nsec: 0,
+            }),
+            UNIX_EPOCH
+        );
+    }
+
+    #[test]
+    fn should_keep_nanoseconds_in_dirent_info() {
+        let name = std::ffi::CString::new("file.txt").unwrap();
+        let info = libsmb_file_info {
+            name: name.as_ptr() as *mut _,
+            short_name: name.as_ptr() as *mut _,
+            btime_ts: timespec {
+                tv_sec: 1_600_000_000,
+                tv_nsec: 1,
+            },
+            mtime_ts: timespec {
+                tv_sec: 1_700_000_000,
+                tv_nsec: 500,
+            },
+            ..Default::default()
+        };
+        let info = SmbDirentInfo::try_from(info).unwrap();
+        assert_eq!(info.mtime, UNIX_EPOCH + Duration::new(1_700_000_000, 500));
+        let stat = SmbStat::from(unsafe { std::mem::zeroed::<stat>() });
+        let dirent = SmbDirentStat::new(info, stat);
+        assert_eq!(
+            dirent.stat.created,
+            UNIX_EPOCH + Duration::new(1_600_000_000, 1)
+        );
+    }
 }
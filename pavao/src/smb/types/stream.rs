@@ -0,0 +1,50 @@
+//! # Stream
+//!
+//! NTFS alternate data streams.
+//!
+//! A named stream is addressed as `path:name`; libsmbclient doesn't expose the stream information class,
+//! so the streams of a file can't be enumerated and must be known by name.
+
+use crate::{SmbError, SmbResult};
+
+/// Named data stream of a file
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SmbStreamInfo {
+    /// Name of the stream, without the file name
+    pub name: String,
+    /// Size of the stream in bytes
+    pub size: u64,
+}
+
+/// Build the path of the stream `name` of the file at `path`
+pub(crate) fn stream_path(path: &str, name: &str) -> SmbResult<String> {
+    if name.is_empty() || name.contains([':', '/', '\\']) {
+        error!("bad stream name {}", name);
+        return Err(SmbError::BadValue);
+    }
+    Ok(format!("{}:{}", path, name))
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_build_stream_path() {
+        assert_eq!(
+            stream_path("/dir/file.txt", "Zone.Identifier").unwrap(),
+            "/dir/file.txt:Zone.Identifier"
+        );
+    }
+
+    #[test]
+    fn should_reject_bad_stream_names() {
+        assert!(stream_path("/file.txt", "").is_err());
+        assert!(stream_path("/file.txt", "stream:$DATA").is_err());
+        assert!(stream_path("/file.txt", "a/b").is_err());
+        assert!(stream_path("/file.txt", "a\\b").is_err());
+    }
+}
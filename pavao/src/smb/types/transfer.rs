@@ -0,0 +1,198 @@
+//! # Transfer
+//!
+//! options, progress and outcome of `upload` and `download`
+
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+use crate::{SmbError, SmbResult};
+
+/// Default size of the buffer used to transfer files
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Options for `upload` and `download`
+#[derive(Debug, Clone, Copy)]
+pub struct SmbTransferOptions {
+    pub(crate) buffer_size: usize,
+    pub(crate) overwrite: bool,
+    pub(crate) preserve_times: bool,
+}
+
+impl Default for SmbTransferOptions {
+    fn default() -> Self {
+        Self {
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            overwrite: false,
+            preserve_times: true,
+        }
+    }
+}
+
+impl SmbTransferOptions {
+    /// Size of the buffer used to read and write the file; must be greater than 0 (default: 64 KiB)
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Replace the destination if it exists (default: `false`).
+    ///
+    /// When `false`, the transfer fails if the destination exists.
+    /// When `true`, the file is written to a temporary sibling which then replaces the destination
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Set the access and modification times of the destination to the ones of the source (default: `true`)
+    pub fn preserve_times(mut self, preserve_times: bool) -> Self {
+        self.preserve_times = preserve_times;
+        self
+    }
+
+    /// Check the options are consistent
+    pub(crate) fn validate(&self) -> SmbResult<()> {
+        if self.buffer_size == 0 {
+            error!("transfer buffer size can't be 0");
+            return Err(SmbError::BadValue);
+        }
+        Ok(())
+    }
+}
+
+/// Progress of a transfer, reported after each written buffer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmbTransferProgress {
+    /// Bytes transferred so far
+    pub transferred: u64,
+    /// Size of the source file, as of the beginning of the transfer
+    pub total: u64,
+    /// Time elapsed since the beginning of the transfer
+    pub elapsed: Duration,
+    /// Average rate in bytes per second
+    pub rate: f64,
+}
+
+impl SmbTransferProgress {
+    fn new(transferred: u64, total: u64, elapsed: Duration) -> Self {
+        let secs = elapsed.as_secs_f64();
+        Self {
+            transferred,
+            total,
+            elapsed,
+            rate: match secs > 0.0 {
+                true => transferred as f64 / secs,
+                false => 0.0,
+            },
+        }
+    }
+}
+
+/// Outcome of a completed transfer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmbTransferSummary {
+    /// Bytes transferred
+    pub bytes: u64,
+    /// Duration of the transfer
+    pub elapsed: Duration,
+    /// Average rate in bytes per second
+    pub rate: f64,
+    /// Whether the times of the source have been set on the destination
+    pub times_preserved: bool,
+}
+
+/// Copy `reader` to `writer` with a buffer of `buffer_size`, reporting the progress after each write
+pub(crate) fn transfer(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    total: u64,
+    buffer_size: usize,
+    progress: &mut dyn FnMut(SmbTransferProgress),
+) -> SmbResult<SmbTransferSummary> {
+    let start = Instant::now();
+    let mut buf = vec![0u8; buffer_size];
+    let mut transferred = 0;
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buf[..n])?;
+        transferred += n as u64;
+        progress(SmbTransferProgress::new(
+            transferred,
+            total,
+            start.elapsed(),
+        ));
+    }
+    writer.flush()?;
+    let done = SmbTransferProgress::new(transferred, total, start.elapsed());
+    Ok(SmbTransferSummary {
+        bytes: done.transferred,
+        elapsed: done.elapsed,
+        rate: done.rate,
+        times_preserved: false,
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::io::Cursor;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_configure_transfer_options() {
+        let options = SmbTransferOptions::default();
+        assert_eq!(options.buffer_size, 64 * 1024);
+        assert_eq!(options.overwrite, false);
+        assert_eq!(options.preserve_times, true);
+        let options = options
+            .buffer_size(1024)
+            .overwrite(true)
+            .preserve_times(false);
+        assert_eq!(options.buffer_size, 1024);
+        assert_eq!(options.overwrite, true);
+        assert_eq!(options.preserve_times, false);
+    }
+
+    #[test]
+    fn should_compute_transfer_rate() {
+        let progress = SmbTransferProgress::new(1024, 4096, Duration::from_secs(2));
+        assert_eq!(progress.rate, 512.0);
+        assert_eq!(
+            SmbTransferProgress::new(1024, 4096, Duration::ZERO).rate,
+            0.0
+        );
+    }
+
+    #[test]
+    fn should_transfer_with_progress() {
+        let data = vec![0xcd; 10_000];
+        let mut reader = Cursor::new(data.clone());
+        let mut writer = Vec::new();
+        let mut reports = Vec::new();
+        let summary = transfer(&mut reader, &mut writer, 10_000, 4096, &mut |p| {
+            reports.push((p.transferred, p.total))
+        })
+        .unwrap();
+        assert_eq!(writer, data);
+        assert_eq!(summary.bytes, 10_000);
+        assert_eq!(
+            reports,
+            vec![(4096, 10_000), (8192, 10_000), (10_000, 10_000)]
+        );
+    }
+
+    #[test]
+    fn should_validate_transfer_options() {
+        assert!(SmbTransferOptions::default().validate().is_ok());
+        assert!(SmbTransferOptions::default()
+            .buffer_size(0)
+            .validate()
+            .is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! # Walk
+//!
+//! recursive directory traversal returned by `walk`
+
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use super::{SmbDirentInfo, SmbFileAttributes};
+use crate::{utils, SmbClient, SmbDirentType, SmbError, SmbResult};
+
+/// Order in which the directory tree is traversed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SmbWalkOrder {
+    /// The content of a directory is yielded right after the directory itself
+    #[default]
+    DepthFirst,
+    /// All the entries at a depth are yielded before the entries at the next depth
+    BreadthFirst,
+}
+
+/// Entry yielded by [`SmbWalk`]
+#[derive(Debug, Clone)]
+pub struct SmbWalkEntry {
+    /// Full path of the entry
+    pub path: String,
+    /// Depth of the entry; direct children of the root have depth 1
+    pub depth: usize,
+    /// Entry metadata
+    pub info: SmbDirentInfo,
+}
+
+impl SmbWalkEntry {
+    /// Get directory entity type
+    pub fn get_type(&self) -> SmbDirentType {
+        self.info.get_type()
+    }
+
+    /// Get name
+    pub fn name(&self) -> &str {
+        self.info.name()
+    }
+
+    /// Returns whether the entry is a reparse point (e.g. a junction or a symlink)
+    pub fn is_reparse_point(&self) -> bool {
+        self.info
+            .attributes()
+            .contains(SmbFileAttributes::REPARSE_POINT)
+    }
+}
+
+type SortFn<'a> = Box<dyn FnMut(&SmbWalkEntry, &SmbWalkEntry) -> Ordering + 'a>;
+type FilterFn<'a> = Box<dyn FnMut(&SmbWalkEntry) -> bool + 'a>;
+
+/// Recursive iterator over a directory tree.
+///
+/// Directories are listed lazily while iterating. The root itself is not yielded.
+/// A directory which can't be listed yields an error with its path, then the walk goes on.
+pub struct SmbWalk<'a> {
+    smbc: &'a SmbClient,
+    root: String,
+    started: bool,
+    pending: VecDeque<SmbResult<SmbWalkEntry>>,
+    min_depth: usize,
+    max_depth: usize,
+    order: SmbWalkOrder,
+    follow_reparse_points: bool,
+    sorter: Option<SortFn<'a>>,
+    filter: Option<FilterFn<'a>>,
+}
+
+impl<'a> SmbWalk<'a> {
+    pub(crate) fn new<S: AsRef<str>>(smbc: &'a SmbClient, root: S) -> Self {
+        Self {
+            smbc,
+            root: root.as_ref().to_string(),
+            started: false,
+            pending: VecDeque::new(),
+            min_depth: 1,
+            max_depth: usize::MAX,
+            order: SmbWalkOrder::default(),
+            follow_reparse_points: false,
+            sorter: None,
+            filter: None,
+        }
+    }
+
+    /// Don't yield entries with a depth lower than `depth` (default: 1)
+    pub fn min_depth(mut self, depth: usize) -> Self {
+        self.min_depth = depth;
+        self
+    }
+
+    /// Don't descend into directories deeper than `depth` (default: unlimited)
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Set the traversal order (default: depth first)
+    pub fn order(mut self, order: SmbWalkOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Descend into directories which are reparse points (default: `false`)
+    pub fn follow_reparse_points(mut self, follow: bool) -> Self {
+        self.follow_reparse_points = follow;
+        self
+    }
+
+    /// Sort the entries of each directory with `cmp`.
+    /// By default entries are yielded in the order returned by the server
+    pub fn sort_by<F>(mut self, cmp: F) -> Self
+    where
+        F: FnMut(&SmbWalkEntry, &SmbWalkEntry) -> Ordering + 'a,
+    {
+        self.sorter = Some(Box::new(cmp));
+        self
+    }
+
+    /// Yield only the entries for which `predicate` returns `true`.
+    ///
+    /// Directories for which `predicate` returns `false` are pruned: their content is not traversed
+    pub fn filter_entry<F>(mut self, predicate: F) -> Self
+    where
+        F: FnMut(&SmbWalkEntry) -> bool + 'a,
+    {
+        self.filter = Some(Box::new(predicate));
+        self
+    }
+
+    /// List directory at `path` and queue its entries at `depth`
+    fn push_dir(&mut self, path: &str, depth: usize) {
+        trace!("walking directory {} at depth {}", path, depth);
+        let mut entries: Vec<SmbWalkEntry> = match self.smbc.list_dirplus(path) {
+            Ok(entries) => entries
+                .into_iter()
+                .map(|info| SmbWalkEntry {
+                    path: utils::join_path(path, info.name()),
+                    depth,
+                    info,
+                })
+                .collect(),
+            Err(e) => {
+                error!("failed to list directory {}: {}", path, e);
+                self.pending.push_front(Err(SmbError::with_path(e, path)));
+                return;
+            }
+        };
+        if let Some(sorter) = self.sorter.as_mut() {
+            entries.sort_by(|a, b| sorter(a, b));
+        }
+        match self.order {
+            SmbWalkOrder::DepthFirst => {
+                for entry in entries.into_iter().rev() {
+                    self.pending.push_front(Ok(entry));
+                }
+            }
+            SmbWalkOrder::BreadthFirst => self.pending.extend(entries.into_iter().map(Ok)),
+        }
+    }
+
+    /// Returns whether the walk must descend into `entry`
+    fn should_descend(&self, entry: &SmbWalkEntry) -> bool {
+        entry.get_type() == SmbDirentType::Dir
+            && entry.depth < self.max_depth
+            && (self.follow_reparse_points || !entry.is_reparse_point())
+    }
+}
+
+impl Iterator for SmbWalk<'_> {
+    type Item = SmbResult<SmbWalkEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            if self.max_depth > 0 {
+                let root = self.root.clone();
+                self.push_dir(&root, 1);
+            }
+        }
+        loop {
+            let entry = match self.pending.pop_front()? {
+                Ok(entry) => entry,
+                Err(e) => return Some(Err(e)),
+            };
+            if let Some(filter) = self.filter.as_mut() {
+                if !filter(&entry) {
+                    trace!("pruning {}", entry.path);
+                    continue;
+                }
+            }
+            if self.should_descend(&entry) {
+                self.push_dir(&entry.path, entry.depth + 1);
+            }
+            if entry.depth >= self.min_depth {
+                return Some(Ok(entry));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_default_walk_order_to_depth_first() {
+        assert_eq!(SmbWalkOrder::default(), SmbWalkOrder::DepthFirst);
+    }
+}
@@ -0,0 +1,334 @@
+//! # Watch
+//!
+//! directory change notifications returned by `watch`
+
+use std::collections::VecDeque;
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bitflags::bitflags;
+use libc::{c_int, c_uint, c_void, size_t};
+use pavao_sys::{
+    smbc_getFunctionClosedir, smbc_getFunctionNotify, smbc_notify_callback_action, SMBCFILE,
+    SMBC_NOTIFY_ACTION_ADDED, SMBC_NOTIFY_ACTION_ADDED_STREAM, SMBC_NOTIFY_ACTION_MODIFIED,
+    SMBC_NOTIFY_ACTION_MODIFIED_STREAM, SMBC_NOTIFY_ACTION_NEW_NAME, SMBC_NOTIFY_ACTION_OLD_NAME,
+    SMBC_NOTIFY_ACTION_REMOVED, SMBC_NOTIFY_ACTION_REMOVED_STREAM,
+};
+
+use crate::{utils, SmbClient, SmbResult};
+
+bitflags! {
+    /// Changes to watch for, as the `FILE_NOTIFY_CHANGE_*` completion filter defined in MS-SMB2
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct SmbNotifyFilter: u32 {
+        const FILE_NAME = 0x0000_0001;
+        const DIR_NAME = 0x0000_0002;
+        const ATTRIBUTES = 0x0000_0004;
+        const SIZE = 0x0000_0008;
+        const LAST_WRITE = 0x0000_0010;
+        const LAST_ACCESS = 0x0000_0020;
+        const CREATION = 0x0000_0040;
+        const EA = 0x0000_0080;
+        const SECURITY = 0x0000_0100;
+        const STREAM_NAME = 0x0000_0200;
+        const STREAM_SIZE = 0x0000_0400;
+        const STREAM_WRITE = 0x0000_0800;
+    }
+}
+
+impl Default for SmbNotifyFilter {
+    /// Files and directories being created, removed, renamed or written
+    fn default() -> Self {
+        Self::FILE_NAME | Self::DIR_NAME | Self::SIZE | Self::LAST_WRITE
+    }
+}
+
+/// Change reported by [`SmbWatch`].
+///
+/// Paths are the full paths of the changed entries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmbWatchEvent {
+    /// An entry has been created
+    Added(String),
+    /// An entry has been removed
+    Removed(String),
+    /// An entry has been modified
+    Modified(String),
+    /// An entry has been renamed
+    Renamed { from: String, to: String },
+    /// A named stream has been added to an entry
+    StreamAdded(String),
+    /// A named stream has been removed from an entry
+    StreamRemoved(String),
+    /// A named stream of an entry has been modified
+    StreamModified(String),
+}
+
+/// Options for `watch`
+#[derive(Debug, Clone, Copy)]
+pub struct SmbWatchOptions {
+    pub(crate) filter: SmbNotifyFilter,
+    pub(crate) recursive: bool,
+    pub(crate) poll_interval: Duration,
+}
+
+impl Default for SmbWatchOptions {
+    fn default() -> Self {
+        Self {
+            filter: SmbNotifyFilter::default(),
+            recursive: false,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl SmbWatchOptions {
+    /// Set the changes to watch for (default: [`SmbNotifyFilter::default`])
+    pub fn filter(mut self, filter: SmbNotifyFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Watch the whole directory tree instead of the directory only (default: `false`)
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Interval at which a watch without changes checks whether it has been stopped (default: 1 second)
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+}
+
+/// Handle to stop a [`SmbWatch`], possibly from another thread
+#[derive(Debug, Clone)]
+pub struct SmbWatchStopper(Arc<AtomicBool>);
+
+impl SmbWatchStopper {
+    /// Stop the watch. The watch iterator returns `None` within the poll interval
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Blocking iterator over the changes in a directory.
+///
+/// Each call to `next` waits until a change is reported by the server.
+/// The iteration ends once the watch is stopped with a [`SmbWatchStopper`];
+/// the directory handle is closed when the iterator is dropped.
+pub struct SmbWatch<'a> {
+    smbc: &'a SmbClient,
+    fd: *mut SMBCFILE,
+    path: String,
+    options: SmbWatchOptions,
+    pending: VecDeque<SmbWatchEvent>,
+    stopped: Arc<AtomicBool>,
+    failed: bool,
+}
+
+/// State passed to the `notify` callback
+struct NotifyState<'a> {
+    path: &'a str,
+    events: &'a mut VecDeque<SmbWatchEvent>,
+    stopped: &'a AtomicBool,
+}
+
+impl<'a> SmbWatch<'a> {
+    pub(crate) fn new<S: AsRef<str>>(
+        smbc: &'a SmbClient,
+        fd: *mut SMBCFILE,
+        path: S,
+        options: SmbWatchOptions,
+    ) -> Self {
+        Self {
+            smbc,
+            fd,
+            path: path.as_ref().to_string(),
+            options,
+            pending: VecDeque::new(),
+            stopped: Arc::new(AtomicBool::new(false)),
+            failed: false,
+        }
+    }
+
+    /// Get a handle to stop the watch
+    pub fn stopper(&self) -> SmbWatchStopper {
+        SmbWatchStopper(self.stopped.clone())
+    }
+
+    /// Wait for the next changes from the server and queue them
+    fn notify(&mut self) -> SmbResult<()> {
+        let ctx = self.smbc.ctx()?;
+        let notify_fn = self.smbc.get_fn(ctx, smbc_getFunctionNotify)?;
+        let timeout = c_uint::try_from(self.options.poll_interval.as_millis())
+            .unwrap_or(c_uint::MAX)
+            .max(1);
+        let mut state = NotifyState {
+            path: &self.path,
+            events: &mut self.pending,
+            stopped: &self.stopped,
+        };
+        let rc = notify_fn(
+            ctx,
+            self.fd,
+            self.options.recursive as c_int,
+            self.options.filter.bits(),
+            timeout,
+            Some(Self::notify_wrapper),
+            &mut state as *mut NotifyState as *mut c_void,
+        );
+        utils::to_result_with_ioerror((), rc)
+    }
+
+    /// Callback passed to `notify`; returning a value other than 0 makes `notify` return
+    extern "C" fn notify_wrapper(
+        actions: *const smbc_notify_callback_action,
+        num_actions: size_t,
+        private: *mut c_void,
+    ) -> c_int {
+        let Some(state) = (unsafe { (private as *mut NotifyState).as_mut() }) else {
+            return 1;
+        };
+        // called without actions when the timeout expires
+        if actions.is_null() || num_actions == 0 {
+            return state.stopped.load(Ordering::SeqCst) as c_int;
+        }
+        let actions: Vec<(u32, String)> = unsafe { slice::from_raw_parts(actions, num_actions) }
+            .iter()
+            .filter_map(|action| {
+                utils::char_ptr_to_string(action.filename)
+                    .ok()
+                    .map(|name| (action.action, name))
+            })
+            .collect();
+        state.events.extend(parse_actions(state.path, &actions));
+        1
+    }
+}
+
+/// Convert the actions reported by the server into events,
+/// pairing the old and new names of renamed entries
+fn parse_actions(path: &str, actions: &[(u32, String)]) -> Vec<SmbWatchEvent> {
+    let mut events = Vec::with_capacity(actions.len());
+    let mut old_name: Option<String> = None;
+    for (action, name) in actions {
+        let name = utils::join_path(path, &name.replace('\\', "/"));
+        if *action != SMBC_NOTIFY_ACTION_NEW_NAME {
+            // a rename without new name is reported as a removal
+            if let Some(old_name) = old_name.take() {
+                events.push(SmbWatchEvent::Removed(old_name));
+            }
+        }
+        match *action {
+            SMBC_NOTIFY_ACTION_ADDED => events.push(SmbWatchEvent::Added(name)),
+            SMBC_NOTIFY_ACTION_REMOVED => events.push(SmbWatchEvent::Removed(name)),
+            SMBC_NOTIFY_ACTION_MODIFIED => events.push(SmbWatchEvent::Modified(name)),
+            SMBC_NOTIFY_ACTION_OLD_NAME => old_name = Some(name),
+            SMBC_NOTIFY_ACTION_NEW_NAME => events.push(match old_name.take() {
+                Some(from) => SmbWatchEvent::Renamed { from, to: name },
+                None => SmbWatchEvent::Added(name),
+            }),
+            SMBC_NOTIFY_ACTION_ADDED_STREAM => events.push(SmbWatchEvent::StreamAdded(name)),
+            SMBC_NOTIFY_ACTION_REMOVED_STREAM => events.push(SmbWatchEvent::StreamRemoved(name)),
+            SMBC_NOTIFY_ACTION_MODIFIED_STREAM => events.push(SmbWatchEvent::StreamModified(name)),
+            action => warn!("ignoring unknown notify action {} for {}", action, name),
+        }
+    }
+    if let Some(old_name) = old_name {
+        events.push(SmbWatchEvent::Removed(old_name));
+    }
+    events
+}
+
+impl Iterator for SmbWatch<'_> {
+    type Item = SmbResult<SmbWatchEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.failed || self.stopped.load(Ordering::SeqCst) {
+                return None;
+            }
+            if let Err(e) = self.notify() {
+                error!("failed to watch directory {}: {}", self.path, e);
+                self.failed = true;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+impl Drop for SmbWatch<'_> {
+    fn drop(&mut self) {
+        trace!("closing watched directory");
+        if let Ok(ctx) = self.smbc.ctx() {
+            if let Ok(closedir_fn) = self.smbc.get_fn(ctx, smbc_getFunctionClosedir) {
+                closedir_fn(ctx, self.fd);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_configure_watch_options() {
+        let options = SmbWatchOptions::default();
+        assert_eq!(options.filter, SmbNotifyFilter::default());
+        assert_eq!(options.recursive, false);
+        assert_eq!(options.poll_interval, Duration::from_secs(1));
+        let options = options
+            .filter(SmbNotifyFilter::FILE_NAME)
+            .recursive(true)
+            .poll_interval(Duration::from_millis(200));
+        assert_eq!(options.filter, SmbNotifyFilter::FILE_NAME);
+        assert_eq!(options.recursive, true);
+        assert_eq!(options.poll_interval, Duration::from_millis(200));
+    }
+
+    #[test]
+    fn should_parse_notify_actions() {
+        let actions = vec![
+            (SMBC_NOTIFY_ACTION_ADDED, "a.txt".to_string()),
+            (SMBC_NOTIFY_ACTION_MODIFIED, "sub\\b.txt".to_string()),
+            (SMBC_NOTIFY_ACTION_OLD_NAME, "a.txt".to_string()),
+            (SMBC_NOTIFY_ACTION_NEW_NAME, "c.txt".to_string()),
+            (SMBC_NOTIFY_ACTION_REMOVED, "c.txt".to_string()),
+            (SMBC_NOTIFY_ACTION_ADDED_STREAM, "c.txt:s".to_string()),
+            (SMBC_NOTIFY_ACTION_OLD_NAME, "d.txt".to_string()),
+        ];
+        assert_eq!(
+            parse_actions("/drop/", &actions),
+            vec![
+                SmbWatchEvent::Added("/drop/a.txt".to_string()),
+                SmbWatchEvent::Modified("/drop/sub/b.txt".to_string()),
+                SmbWatchEvent::Renamed {
+                    from: "/drop/a.txt".to_string(),
+                    to: "/drop/c.txt".to_string()
+                },
+                SmbWatchEvent::Removed("/drop/c.txt".to_string()),
+                SmbWatchEvent::StreamAdded("/drop/c.txt:s".to_string()),
+                SmbWatchEvent::Removed("/drop/d.txt".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_unpaired_new_name_as_added() {
+        assert_eq!(
+            parse_actions("/", &[(SMBC_NOTIFY_ACTION_NEW_NAME, "a".to_string())]),
+            vec![SmbWatchEvent::Added("/a".to_string())]
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! # Xattr
+//!
+//! module which exposes the types used to work with extended attributes
+
+use libc::c_int;
+
+/// `SMBC_XATTR_FLAG_CREATE` as defined in libsmbclient.h
+const XATTR_FLAG_CREATE: c_int = 0x1;
+/// `SMBC_XATTR_FLAG_REPLACE` as defined in libsmbclient.h
+const XATTR_FLAG_REPLACE: c_int = 0x2;
+
+/// Describes how `set_xattr` behaves if the attribute already exists or not
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SmbXattrFlag {
+    /// Create the attribute or replace its value if it already exists
+    #[default]
+    Any,
+    /// Fail if the attribute already exists
+    Create,
+    /// Fail if the attribute doesn't exist
+    Replace,
+}
+
+impl From<SmbXattrFlag> for c_int {
+    fn from(flag: SmbXattrFlag) -> Self {
+        match flag {
+            SmbXattrFlag::Any => 0,
+            SmbXattrFlag::Create => XATTR_FLAG_CREATE,
+            SmbXattrFlag::Replace => XATTR_FLAG_REPLACE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_convert_xattr_flag_to_c_int() {
+        assert_eq!(c_int::from(SmbXattrFlag::Any), 0);
+        assert_eq!(c_int::from(SmbXattrFlag::Create), 1);
+        assert_eq!(c_int::from(SmbXattrFlag::Replace), 2);
+        assert_eq!(SmbXattrFlag::default(), SmbXattrFlag::Any);
+    }
+}
@@ -6,11 +6,50 @@ use crate::{SmbClient, SmbCredentials, SmbMode, SmbOptions};
 
 pub struct TestCtx {
     pub client: SmbClient,
-    _container: SambaContainer,
+    container: SambaContainer,
 }
 
 impl Default for TestCtx {
     fn default() -> Self {
+        Self::with_share("/temp")
+    }
+}
+
+impl TestCtx {
+    /// Run `script` on the server
+    pub fn exec(&self, script: &str) {
+        self.container.exec(script);
+    }
+
+    pub fn with_share(share: &str) -> Self {
+        Self::new(
+            share,
+            SmbOptions::default()
+                .case_sensitive(true)
+                .one_share_per_server(true),
+        )
+    }
+
+    pub fn with_options(options: SmbOptions) -> Self {
+        Self::new("/temp", options)
+    }
+
+    pub fn new(share: &str, options: SmbOptions) -> Self {
+        let ctx = Self::connect(share, options);
+        // create /cargo-test
+        ctx.client
+            .mkdir("/cargo-test", SmbMode::from(0o777))
+            .expect("failed to create test dir");
+
+        ctx
+    }
+
+    /// Connect to the print queue `share`, where directories can't be created
+    pub fn with_print_queue(share: &str) -> Self {
+        Self::connect(share, SmbOptions::default().one_share_per_server(true))
+    }
+
+    fn connect(share: &str, options: SmbOptions) -> Self {
         let _ = env_logger::builder()
             .is_test(true)
             .filter_level(log::LevelFilter::Debug)
@@ -18,30 +57,17 @@ impl Default for TestCtx {
 
         let container = SambaContainer::start();
 
-        let port = container.get_smb_port();
-        let url = format!("smb://localhost:{}", port);
-
         let client = SmbClient::new(
             SmbCredentials::default()
-                .server(&url)
-                .share("/temp")
+                .server("smb://localhost")
+                .share(share)
                 .username("test")
                 .password("test")
                 .workgroup("pavao"),
-            SmbOptions::default()
-                .case_sensitive(true)
-                .one_share_per_server(true),
+            options.port(container.get_smb_port()),
         )
         .expect("failed to create client");
 
-        // create /cargo-test
-        client
-            .mkdir("/cargo-test", SmbMode::from(0o777))
-            .expect("failed to create test dir");
-
-        TestCtx {
-            client,
-            _container: container,
-        }
+        TestCtx { client, container }
     }
 }
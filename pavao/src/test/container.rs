@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use testcontainers::core::{ContainerPort, WaitFor};
+use testcontainers::core::{CmdWaitFor, ContainerPort, ExecCommand, WaitFor};
 use testcontainers::{Container, Image};
 
 #[derive(Debug, Default, Clone)]
@@ -27,6 +27,26 @@ impl Image for SambaImage {
             "-p",
             "-s",
             "temp;/mnt/tmp;yes;no;yes;test;test",
+            "-s",
+            "other;/mnt/other;yes;no;yes;test;test",
+            "-s",
+            "streams;/mnt/streams;yes;no;yes;test;test",
+            "-G",
+            "streams;vfs objects = catia fruit streams_xattr",
+            "-s",
+            "snapshots;/mnt/snapshots;yes;no;yes;test;test",
+            "-G",
+            "snapshots;vfs objects = shadow_copy2",
+            "-G",
+            "snapshots;shadow:mountpoint = /mnt/snapshots",
+            "-G",
+            "snapshots;shadow:snapdir = .snapshots",
+            "-s",
+            "printer;/mnt/printer;yes;no;yes;test;test",
+            "-G",
+            "printer;printable = yes",
+            "-G",
+            "printer;print command = mv %s %s.printed",
             "-w",
             "pavao",
         ]
@@ -45,13 +65,21 @@ pub struct SambaContainer {
 impl SambaContainer {
     pub fn start() -> Self {
         use testcontainers::runners::SyncRunner;
-        let container = SambaImage::default()
-            .start()
-            .expect("failed to start container");
+        let container = SambaImage.start().expect("failed to start container");
 
         Self { container }
     }
 
+    /// Run `script` with `sh` in the container, panicking if it fails
+    pub fn exec(&self, script: &str) {
+        self.container
+            .exec(
+                ExecCommand::new(["sh", "-c", script])
+                    .with_cmd_ready_condition(CmdWaitFor::exit_code(0)),
+            )
+            .expect("failed to run command in container");
+    }
+
     pub fn get_smb_port(&self) -> u16 {
         self.container.get_host_port_ipv4(445).expect("no port")
     }
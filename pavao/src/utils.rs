@@ -6,8 +6,9 @@ use std::borrow::Cow;
 use std::ffi::{CStr, CString};
 use std::io::{self, Write};
 use std::slice;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use libc::{c_char, c_int};
+use libc::{c_char, c_int, suseconds_t, time_t, timeval};
 
 use super::SmbResult;
 use crate::SmbError;
@@ -56,6 +57,42 @@ pub fn last_os_error() -> SmbError {
     SmbError::Io(io::Error::last_os_error())
 }
 
+/// Get the raw errno of the last os error
+#[inline(always)]
+pub fn last_errno() -> c_int {
+    io::Error::last_os_error().raw_os_error().unwrap_or(0)
+}
+
+/// Reset errno, to tell whether a call which doesn't report errors in its return value failed
+#[inline(always)]
+pub fn clear_errno() {
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+    unsafe {
+        *libc::__error() = 0
+    };
+    #[cfg(any(target_os = "android", target_os = "openbsd", target_os = "netbsd"))]
+    unsafe {
+        *libc::__errno() = 0
+    };
+    #[cfg(any(target_os = "solaris", target_os = "illumos"))]
+    unsafe {
+        *libc::___errno() = 0
+    };
+    #[cfg(not(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "android",
+        target_os = "openbsd",
+        target_os = "netbsd",
+        target_os = "solaris",
+        target_os = "illumos"
+    )))]
+    unsafe {
+        *libc::__errno_location() = 0
+    };
+}
+
 /// Given the return value of a smb function, it returns the last OS error in case the ret_val is equal to -1
 /// otherwise return `Ok(ok_val)`
 #[inline(always)]
@@ -107,6 +144,63 @@ pub fn char_ptr_to_string(ptr: *const c_char) -> SmbResult<String> {
         .map_err(|_| SmbError::BadValue)
 }
 
+/// Join a directory path and an entry name
+#[inline(always)]
+pub fn join_path(dir: &str, name: &str) -> String {
+    format!("{}/{}", dir.trim_end_matches('/'), name)
+}
+
+/// Convert a system time to a timeval; times before the unix epoch are clamped to it
+#[inline(always)]
+pub fn system_time_to_timeval(t: SystemTime) -> timeval {
+    let d = t.duration_since(UNIX_EPOCH).unwrap_or_default();
+    timeval {
+        tv_sec: d.as_secs() as time_t,
+        tv_usec: d.subsec_micros() as suseconds_t,
+    }
+}
+
+/// Parse a time xattr value, in seconds since the unix epoch with an optional fraction.
+///
+/// A zero time means the server doesn't report it
+pub fn parse_xattr_time(value: &str) -> SmbResult<SystemTime> {
+    let value = value.trim_end_matches('\0').trim();
+    let (secs, frac) = value.split_once('.').unwrap_or((value, ""));
+    let secs: u64 = secs.parse().map_err(|_| SmbError::BadValue)?;
+    let nanos: u32 = match frac.len() {
+        0 => 0,
+        1..=9 if frac.bytes().all(|b| b.is_ascii_digit()) => format!("{:0<9}", frac)
+            .parse()
+            .map_err(|_| SmbError::BadValue)?,
+        _ => return Err(SmbError::BadValue),
+    };
+    match (secs, nanos) {
+        (0, 0) => Err(SmbError::NotSupported),
+        (secs, nanos) => Ok(UNIX_EPOCH + Duration::new(secs, nanos)),
+    }
+}
+
+/// Suffix of the temporary sibling a file is written to before replacing the original
+pub fn partial_suffix() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!(".pavao-{}.part", nanos)
+}
+
+/// Map a samba debug level to a log level
+#[inline(always)]
+pub fn debug_level_to_log_level(level: c_int) -> log::Level {
+    match level {
+        i32::MIN..=0 => log::Level::Error,
+        1 => log::Level::Warn,
+        2..=3 => log::Level::Info,
+        4..=5 => log::Level::Debug,
+        _ => log::Level::Trace,
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -128,4 +222,65 @@ mod test {
             "Hello, World!"
         );
     }
+
+    #[test]
+    fn should_join_path() {
+        assert_eq!(join_path("/a/b", "c"), "/a/b/c");
+        assert_eq!(join_path("/a/b/", "c"), "/a/b/c");
+        assert_eq!(join_path("/", "c"), "/c");
+        assert_eq!(join_path("", "c"), "/c");
+    }
+
+    #[test]
+    fn should_convert_system_time_to_timeval() {
+        let t = UNIX_EPOCH + std::time::Duration::from_micros(1_700_000_000_123_456);
+        let tv = system_time_to_timeval(t);
+        assert_eq!(tv.tv_sec, 1_700_000_000);
+        assert_eq!(tv.tv_usec, 123_456);
+        assert_eq!(
+            system_time_to_timeval(UNIX_EPOCH - std::time::Duration::from_secs(1)).tv_sec,
+            0
+        );
+    }
+
+    #[test]
+    fn should_clear_errno() {
+        assert!(std::fs::metadata("/this/path/does/not/exist").is_err());
+        clear_errno();
+        assert_eq!(last_errno(), 0);
+    }
+
+    #[test]
+    fn should_parse_xattr_time() {
+        assert_eq!(
+            parse_xattr_time("1700000000\0").unwrap(),
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+        );
+        assert_eq!(
+            parse_xattr_time("1700000000.25").unwrap(),
+            UNIX_EPOCH + Duration::new(1_700_000_000, 250_000_000)
+        );
+        assert!(matches!(
+            parse_xattr_time("0").unwrap_err(),
+            SmbError::NotSupported
+        ));
+        assert!(matches!(
+            parse_xattr_time("abc").unwrap_err(),
+            SmbError::BadValue
+        ));
+        assert!(matches!(
+            parse_xattr_time("1.x").unwrap_err(),
+            SmbError::BadValue
+        ));
+    }
+
+    #[test]
+    fn should_map_debug_level_to_log_level() {
+        assert_eq!(debug_level_to_log_level(0), log::Level::Error);
+        assert_eq!(debug_level_to_log_level(1), log::Level::Warn);
+        assert_eq!(debug_level_to_log_level(2), log::Level::Info);
+        assert_eq!(debug_level_to_log_level(3), log::Level::Info);
+        assert_eq!(debug_level_to_log_level(5), log::Level::Debug);
+        assert_eq!(debug_level_to_log_level(10), log::Level::Trace);
+    }
 }